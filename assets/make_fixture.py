@@ -0,0 +1,131 @@
+#!/usr/bin/env python3
+"""Generates `Fixture-VarColr.ttf`, a tiny test font with a weight axis and a COLR v0 glyph.
+
+Glyphs (1000 units per em):
+  1 'A': square 100..500 x 0..400, advance 600. At `wght` 900 the right edge
+         and the advance move by +200 units (gvar, including phantom points).
+  2 'B': color glyph made of the layers 3 (palette entry 0, opaque red),
+         4 (foreground, palette index 0xFFFF) and 5 (palette entry 1, transparent black).
+  3-5:   squares used as layers.
+"""
+
+import struct
+import sys
+
+UNITS_PER_EM = 1000
+
+
+def square(x0, y0, x1, y1):
+    points = [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+    data = struct.pack(">hhhhhHH", 1, x0, y0, x1, y1, len(points) - 1, 0)
+    data += bytes([0x01] * len(points))
+    last = 0
+    for x, _ in points:
+        data += struct.pack(">h", x - last)
+        last = x
+    last = 0
+    for _, y in points:
+        data += struct.pack(">h", y - last)
+        last = y
+    return data
+
+
+def pad(data, n=4):
+    return data + b"\0" * (-len(data) % n)
+
+
+glyphs = [
+    b"",
+    square(100, 0, 500, 400),
+    square(0, 0, 600, 600),
+    square(0, 0, 300, 300),
+    square(200, 200, 500, 500),
+    square(300, 300, 600, 600),
+]
+advances = [500, 600, 700, 700, 700, 700]
+num_glyphs = len(glyphs)
+
+glyf = b""
+loca = []
+for glyph in glyphs:
+    loca.append(len(glyf))
+    glyf += pad(glyph)
+loca.append(len(glyf))
+
+tables = {}
+tables[b"head"] = struct.pack(
+    ">IIIIHHqqhhhhHHhhh",
+    0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0, UNITS_PER_EM, 0, 0,
+    0, 0, 600, 600, 0, 8, 2, 1, 0,
+)
+tables[b"hhea"] = struct.pack(
+    ">IhhhHhhhhhh8xhH",
+    0x00010000, 800, -200, 0, max(advances), 0, 0, 600, 1, 0, 0, 0, num_glyphs,
+)
+tables[b"maxp"] = struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, num_glyphs, 4, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0)
+tables[b"hmtx"] = b"".join(struct.pack(">Hh", advance, 0) for advance in advances)
+tables[b"loca"] = b"".join(struct.pack(">I", offset) for offset in loca)
+tables[b"glyf"] = glyf
+
+# cmap: format 4 mapping 'A'..'B' to glyphs 1..2.
+seg_count = 2
+subtable = struct.pack(
+    ">HHHHHHH", 4, 16 + 8 * seg_count, 0, 2 * seg_count, 4, 1, 0,
+)
+subtable += struct.pack(">HH", 0x42, 0xFFFF) + struct.pack(">H", 0)
+subtable += struct.pack(">HH", 0x41, 0xFFFF)
+subtable += struct.pack(">HH", (1 - 0x41) & 0xFFFF, 1)
+subtable += struct.pack(">HH", 0, 0)
+tables[b"cmap"] = struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+# fvar: single weight axis 100..900, default 400.
+tables[b"fvar"] = struct.pack(">HHHHHHHH", 1, 0, 16, 2, 1, 20, 0, 8) + struct.pack(
+    ">4siiiHH", b"wght", 100 << 16, 400 << 16, 900 << 16, 0, 256
+)
+
+# gvar: glyph 1 moves its right edge and right phantom point at the max weight.
+x_deltas = [0, 0, 200, 200, 0, 200, 0, 0]
+deltas = bytes([0x40 | (len(x_deltas) - 1)]) + b"".join(struct.pack(">h", d) for d in x_deltas)
+deltas += bytes([0x80 | (len(x_deltas) - 1)])
+tuple_header = struct.pack(">HHh", len(deltas), 0x8000, 0x4000)
+variation = pad(struct.pack(">HH", 1, 4 + len(tuple_header)) + tuple_header + deltas, 2)
+glyph_data = [b"", variation] + [b""] * (num_glyphs - 2)
+offsets = [0]
+for data in glyph_data:
+    offsets.append(offsets[-1] + len(data))
+header_size = 20 + 2 * (num_glyphs + 1)
+tables[b"gvar"] = (
+    struct.pack(">HHHHIHHI", 1, 0, 1, 0, header_size, num_glyphs, 0, header_size)
+    + b"".join(struct.pack(">H", offset // 2) for offset in offsets)
+    + b"".join(glyph_data)
+)
+
+# COLR v0: glyph 2 made of three layers.
+layers = [(3, 0), (4, 0xFFFF), (5, 1)]
+tables[b"COLR"] = (
+    struct.pack(">HHIIH", 0, 1, 14, 20, len(layers))
+    + struct.pack(">HHH", 2, 0, len(layers))
+    + b"".join(struct.pack(">HH", glyph, index) for glyph, index in layers)
+)
+
+# CPAL: one palette, opaque red and transparent black (BGRA).
+tables[b"CPAL"] = struct.pack(">HHHHIH", 0, 2, 1, 2, 14, 0) + bytes([0, 0, 255, 255, 0, 0, 0, 0])
+
+
+def checksum(data):
+    data = pad(data)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+tags = sorted(tables)
+offset = 12 + 16 * len(tags)
+directory = struct.pack(">IHHHH", 0x00010000, len(tags), 128, 3, 16 * len(tags) - 128)
+body = b""
+for tag in tags:
+    data = tables[tag]
+    directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+    body += pad(data)
+
+path = sys.argv[1] if len(sys.argv) > 1 else "Fixture-VarColr.ttf"
+with open(path, "wb") as f:
+    f.write(directory + body)
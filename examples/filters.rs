@@ -6,7 +6,13 @@ const HEIGHT: u32 = 128;
 fn main() {
     let mut app = runic::App::new(WIDTH, HEIGHT, runic::Scale::X1);
 
-    app.add_rasterizer(runic::Key::F1, runic::CoarseRasterizer { filter: runic::StepFilter }, runic::UniformSampler { nx: 32, ny: 32 });
+    app.add_rasterizer(runic::Key::F1, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::UniformSampler { nx: 32, ny: 32 });
+    app.add_rasterizer(runic::Key::F2, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::MsaaSampler::new(runic::MsaaSampleCount::X2));
+    app.add_rasterizer(runic::Key::F3, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::MsaaSampler::new(runic::MsaaSampleCount::X4));
+    app.add_rasterizer(runic::Key::F4, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::MsaaSampler::new(runic::MsaaSampleCount::X8));
+    app.add_rasterizer(runic::Key::F5, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::MsaaSampler::new(runic::MsaaSampleCount::X16));
+    app.add_rasterizer(runic::Key::F6, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::JitteredSampler { sampler: runic::UniformSampler { nx: 2, ny: 2 }, jitter: runic::SampleJitter::Scramble { seed: 0 } });
+    app.add_rasterizer(runic::Key::F7, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::JitteredSampler { sampler: runic::UniformSampler { nx: 2, ny: 2 }, jitter: runic::SampleJitter::blue_noise(32, 0) });
 
     app.add_scene(runic::Key::Key1, render_scene0);
 
@@ -32,12 +38,7 @@ fn render_scene0(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Frame
 
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(4.0, 4.0),
-            extent_local: glam::vec2(20.0, 100.0),
-            offset_curve: aabb_triangle0.min,
-            extent_curve: aabb_triangle0.max - aabb_triangle0.min,
-        },
+        aabb_triangle0.to_rect(glam::vec2(4.0, 4.0), glam::vec2(20.0, 100.0)),
         &path_triangle0,
     );
 }
@@ -1,12 +1,29 @@
 use runic::{Rasterizer};
-use glyph_brush_layout::{SectionGeometry, SectionText, GlyphPositioner};
-use rusttype::{Segment, Font, Scale};
+use std::cell::RefCell;
 
 const WIDTH: u32 = 480;
 const HEIGHT: u32 = 260;
 
+/// Horizontal glyph placement precision of the text scenes.
+const SUBPIXEL_STEPS: u32 = 4;
+
+/// Counteracts thin looking small text after sRGB conversion.
+const TEXT_OPTIONS: runic::TextRenderOptions = runic::TextRenderOptions {
+    stem_darkening: true,
+    gamma: 1.0,
+    contrast: 0.25,
+};
+
 const ROBOTO: &[u8] = include_bytes!("../assets/Roboto-Regular.ttf");
 
+thread_local! {
+    static GLYPH_CACHE: RefCell<runic::GlyphCache> = {
+        let mut cache = runic::GlyphCache::new(512, 512, SUBPIXEL_STEPS);
+        cache.set_options(TEXT_OPTIONS);
+        RefCell::new(cache)
+    };
+}
+
 fn main() {
     let mut app = runic::App::new(WIDTH, HEIGHT, runic::Scale::X2);
 
@@ -20,6 +37,8 @@ fn main() {
     // app.add_rasterizer(runic::Key::F3, runic::DistanceRasterizer { filter: runic::RadialBoxFilter { radius: 0.7 } }, runic::UniformSampler { nx: 1, ny: 1 });
     // app.add_rasterizer(runic::Key::F4, runic::CoarseRasterizer { filter: runic::StepFilter }, runic::UniformSampler { nx: 1, ny: 1 });
     // app.add_rasterizer(runic::Key::F5, runic::CoarseRasterizer { filter: runic::StepFilter }, runic::UniformSampler { nx: 8, ny: 8 });
+    // Three horizontal samples per pixel for subpixel rendering (`L`)
+    app.add_rasterizer(runic::Key::F5, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::BoxFilter::new(-0.5, 0.5) }, runic::UniformSampler { nx: 3, ny: 1 });
     app.add_rasterizer(runic::Key::F6, runic::AnalyticBoxRasterizer, runic::UniformSampler { nx: 1, ny: 1 });
     app.add_rasterizer(runic::Key::F7, runic::DistanceRasterizer { filter: runic::Smoothstep { e0: -0.7, e1: 0.7 } }, runic::UniformSampler { nx: 1, ny: 1 });
     app.add_rasterizer(runic::Key::F8, runic::GouacheRasterizer { filter: runic::Smoothstep { e0: -0.5, e1: 0.5 } }, runic::UniformSampler { nx: 1, ny: 1 });
@@ -31,6 +50,9 @@ fn main() {
     app.add_scene(runic::Key::Key3, render_scene2);
     app.add_scene(runic::Key::Key4, render_scene3);
     app.add_scene(runic::Key::Key5, render_scene4);
+    app.add_scene(runic::Key::Key6, render_scene5);
+    app.add_scene(runic::Key::Key7, render_scene6);
+    app.add_scene(runic::Key::Key8, render_scene7);
 
 
     app.add_filter(runic::Key::N, runic::BoxFilter::new(-0.5, 0.5));
@@ -135,53 +157,28 @@ fn render_scene1(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Frame
 
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(10.0, 20.0),
-            extent_local: glam::vec2(100.0, 25.0),
-            offset_curve: dbg!(aabb_line0.min),
-            extent_curve: dbg!(aabb_line0.max - aabb_line0.min),
-        },
+        aabb_line0.to_rect(glam::vec2(10.0, 20.0), glam::vec2(100.0, 25.0)),
         &path_line0,
     );
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(120.0, 20.0),
-            extent_local: glam::vec2(100.0, 25.0),
-            offset_curve: aabb_line1.min,
-            extent_curve: aabb_line1.max - aabb_line1.min,
-        },
+        aabb_line1.to_rect(glam::vec2(120.0, 20.0), glam::vec2(100.0, 25.0)),
         &path_line1,
     );
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(120.0, 50.0),
-            extent_local: glam::vec2(100.0, 25.0),
-            offset_curve: aabb_line2.min,
-            extent_curve: aabb_line2.max - aabb_line2.min,
-        },
+        aabb_line2.to_rect(glam::vec2(120.0, 50.0), glam::vec2(100.0, 25.0)),
         &path_line2,
     );
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(10.0, 50.0),
-            extent_local: glam::vec2(100.0, 25.0),
-            offset_curve: aabb_line3.min,
-            extent_curve: aabb_line3.max - aabb_line3.min,
-        },
+        aabb_line3.to_rect(glam::vec2(10.0, 50.0), glam::vec2(100.0, 25.0)),
         &path_line3,
     );
 
     rasterizer.cmd_draw(
         framebuffer,
-        runic::Rect {
-            offset_local: glam::vec2(240.0, 20.0),
-            extent_local: glam::vec2(100.0, 100.0),
-            offset_curve: aabb_quad0.min,
-            extent_curve: aabb_quad0.max - aabb_quad0.min,
-        },
+        aabb_quad0.to_rect(glam::vec2(240.0, 20.0), glam::vec2(100.0, 100.0)),
         &path_quad0,
     );
     rasterizer.cmd_draw(
@@ -253,68 +250,101 @@ fn render_scene3(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Frame
 }
 
 fn render_scene4(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Framebuffer) {
+    let font = runic::Font::from_bytes(ROBOTO).unwrap();
+
     let mut offset_y = 0.0;
     for size in &[18.0, 24.0, 32.0, 48.0, 72.0] {
-        let font = Font::from_bytes(&ROBOTO).unwrap();
-        let glyphs = glyph_brush_layout::Layout::default().calculate_glyphs(
-            &[font],
-            &SectionGeometry {
-                screen_position: (10.0, offset_y),
-                ..SectionGeometry::default()
+        let layout = runic::Layout::new(*size).with_line_height(0.8);
+        let glyphs = layout.layout(&font, "quick brown fox", glam::vec2(10.0, offset_y));
+        runic::draw_glyphs(rasterizer, framebuffer, &font, &glyphs, *size, SUBPIXEL_STEPS, &TEXT_OPTIONS, 1.0);
+
+        offset_y += layout.line_advance(&font);
+    }
+}
+
+fn render_scene5(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Framebuffer) {
+    // Material design "favorite" icon
+    let segments = runic::parse_svg_path(
+        "M12 21.35l-1.45-1.32C5.4 15.36 2 12.28 2 8.5 2 5.42 4.42 3 7.5 3c1.74 0 3.41.81 4.5 2.09C13.09 3.81 14.76 3 16.5 3 19.58 3 22 5.42 22 8.5c0 3.78-3.4 6.86-8.55 11.54L12 21.35z",
+        0.01,
+    )
+    .unwrap();
+    let segments = segments
+        .iter()
+        .map(|segment| runic::Curve::monotize_path(segment))
+        .collect::<Vec<_>>();
+
+    let path = rasterizer.create_path(&segments);
+
+    let mut offset_x = 10.0;
+    for size in &[24.0, 48.0, 96.0, 192.0] {
+        rasterizer.cmd_draw(
+            framebuffer,
+            runic::Rect {
+                offset_local: glam::vec2(offset_x, 10.0),
+                extent_local: glam::vec2(*size, *size),
+                offset_curve: glam::vec2(0.0, 0.0),
+                extent_curve: glam::vec2(24.0, 24.0),
             },
-            &[
-                SectionText {
-                    text: "quick brown fox",
-                    scale: Scale::uniform(*size),
-                    ..SectionText::default()
-                },
-            ],
+            &path,
         );
+        offset_x += size + 10.0;
+    }
+}
 
-        offset_y += size;
-
-        for (glyph, _, font) in glyphs {
-            let mut path = runic::PathBuilder::new();
-
-            let bbox = glyph.unpositioned().exact_bounding_box().unwrap();
-            let shapes = glyph.unpositioned().shape().unwrap();
-            let mut pos = glyph.position();
-            pos.y -= bbox.max.y + bbox.min.y;
-
-            for shape in shapes {
-                for segment in &shape.segments {
-                    match segment {
-                        Segment::Line(line) => {
-                            path = path.move_to(glam::vec2(line.p[0].x - bbox.min.x, line.p[0].y + bbox.max.y));
-                            path = path.line_to(glam::vec2(line.p[1].x - bbox.min.x, line.p[1].y + bbox.max.y));
-                        }
-                        Segment::Curve(curve) => {
-                            path = path.move_to(glam::vec2(curve.p[0].x - bbox.min.x, curve.p[0].y + bbox.max.y));
-                            path = path.quad_to(
-                                glam::vec2(curve.p[1].x - bbox.min.x, curve.p[1].y + bbox.max.y),
-                                glam::vec2(curve.p[2].x - bbox.min.x, curve.p[2].y + bbox.max.y)
-                            );
-                        }
-                    }
+fn render_scene6(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Framebuffer) {
+    // Paragraph of repeated glyphs, rasterized once and composited from the glyph cache.
+    let font = runic::Font::from_bytes(ROBOTO).unwrap();
+    let text = "the quick brown fox jumps over the lazy dog";
+
+    GLYPH_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let mut offset_y = 20.0;
+        for size in &[11.0, 13.0, 16.0, 20.0] {
+            let scale = size / font.units_per_em();
+            for _ in 0..3 {
+                let mut x = 10.0;
+                for c in text.chars() {
+                    let glyph = match font.glyph_id(c) {
+                        Some(glyph) => glyph,
+                        None => continue,
+                    };
+                    cache.draw(rasterizer, framebuffer, 0, &font, glyph, *size, glam::vec2(x, offset_y), 1.0);
+                    x += font.advance(glyph) * scale;
                 }
+                offset_y += size * 1.2;
             }
+        }
+    });
+}
 
-            path = path.monotonize();
-            let mut curves = path.finish();
+fn render_scene7(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Framebuffer) {
+    // Shape primitives, curves approximated by quads.
+    let segments = vec![runic::PathBuilder::new()
+        .with_tolerance(0.05)
+        .rounded_rect(glam::vec2(0.0, 0.0), glam::vec2(120.0, 80.0), [0.0, 10.0, 30.0, 20.0])
+        .circle(glam::vec2(190.0, 40.0), 40.0)
+        .ellipse(glam::vec2(330.0, 40.0), glam::vec2(60.0, 25.0))
+        .regular_polygon(glam::vec2(60.0, 160.0), 50.0, 6)
+        .star(glam::vec2(190.0, 160.0), 50.0, 20.0, 5)
+        // Three quarter pie.
+        .move_to(glam::vec2(330.0, 160.0))
+        .line_to(glam::vec2(330.0, 110.0))
+        .arc_to(glam::vec2(50.0, 50.0), 0.0, true, false, glam::vec2(380.0, 160.0))
+        .close()
+        .monotonize()
+        .finish()];
 
-            let rect = runic::Rect {
-                offset_local: glam::vec2(pos.x + bbox.min.x as f32, pos.y + bbox.min.y as f32),
-                extent_local: glam::vec2(bbox.max.x as f32 - bbox.min.x as f32, bbox.max.y as f32 - bbox.min.y as f32),
-                offset_curve: glam::vec2(0.0, 0.0),
-                extent_curve: glam::vec2(bbox.max.x as f32 - bbox.min.x as f32, bbox.max.y as f32 - bbox.min.y as f32),
-            };
-
-            rasterizer.cmd_draw(
-                framebuffer,
-                rect,
-                &curves,
-            );
-        }
-    }
+    let path = rasterizer.create_path(&segments);
 
-}
\ No newline at end of file
+    rasterizer.cmd_draw(
+        framebuffer,
+        runic::Rect {
+            offset_local: glam::vec2(20.0, 20.0),
+            extent_local: glam::vec2(440.0, 220.0),
+            offset_curve: glam::vec2(0.0, 0.0),
+            extent_curve: glam::vec2(440.0, 220.0),
+        },
+        &path,
+    );
+}
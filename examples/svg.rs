@@ -0,0 +1,44 @@
+use runic::Rasterizer;
+
+const WIDTH: u32 = 480;
+const HEIGHT: u32 = 260;
+
+const DEFAULT_DOCUMENT: &str = "assets/shapes.svg";
+
+fn main() {
+    let mut app = runic::App::new(WIDTH, HEIGHT, runic::Scale::X2);
+
+    app.add_rasterizer(runic::Key::F1, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::BoxFilter::new(-0.5, 0.5) }, runic::UniformSampler { nx: 1, ny: 1 });
+    app.add_rasterizer(runic::Key::F2, runic::CoarseRasterizer { direction: runic::CoarseDirection::XY, filter: runic::Smoothstep { e0: -0.5, e1: 0.5 } }, runic::UniformSampler { nx: 1, ny: 1 });
+    app.add_rasterizer(runic::Key::F3, runic::CoarseRasterizer { direction: runic::CoarseDirection::Y, filter: runic::StepFilter }, runic::MsaaSampler::new(runic::MsaaSampleCount::X16));
+    app.add_rasterizer(runic::Key::F6, runic::AnalyticBoxRasterizer, runic::UniformSampler { nx: 1, ny: 1 });
+    app.add_rasterizer(runic::Key::F7, runic::DistanceRasterizer { filter: runic::Smoothstep { e0: -0.7, e1: 0.7 } }, runic::UniformSampler { nx: 1, ny: 1 });
+    app.add_rasterizer(runic::Key::F8, runic::GouacheRasterizer { filter: runic::Smoothstep { e0: -0.5, e1: 0.5 } }, runic::UniformSampler { nx: 1, ny: 1 });
+
+    app.add_scene(runic::Key::Key1, render_document);
+
+    app.add_filter(runic::Key::N, runic::BoxFilter::new(-0.5, 0.5));
+    app.add_filter(runic::Key::B, runic::TentFilter);
+
+    app.run();
+}
+
+fn render_document(rasterizer: &mut dyn Rasterizer, framebuffer: &mut runic::Framebuffer) {
+    let path = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_DOCUMENT.into());
+    let document = match runic::SvgDocument::load(&path, 0.01) {
+        Ok(document) => document,
+        Err(err) => {
+            println!("{}: {}", path, err);
+            return;
+        }
+    };
+
+    for warning in &document.warnings {
+        println!("{}: {}", path, warning);
+    }
+
+    // Fit the document into the window, preserving the aspect ratio.
+    let extent = document.view_box_extent;
+    let scale = (WIDTH as f32 / extent.x()).min(HEIGHT as f32 / extent.y());
+    document.render(rasterizer, framebuffer, glam::vec2(0.0, 0.0), extent * scale);
+}
@@ -0,0 +1,91 @@
+//! Shelf packing of rectangles into a fixed size texture.
+
+pub(crate) struct Shelf {
+    pub y: u32,
+    pub height: u32,
+    pub cursor: u32,
+    /// Most recent use of any rectangle in the shelf.
+    #[cfg_attr(not(feature = "text"), allow(dead_code))]
+    pub last_used: u64,
+}
+
+/// Places rectangles left to right on shelves stacked from top to bottom.
+pub(crate) struct ShelfPacker {
+    pub width: u32,
+    pub height: u32,
+    pub shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        ShelfPacker {
+            width,
+            height,
+            shelves: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+
+    /// Reserve space for a `width` x `height` rectangle, returns the shelf and position.
+    pub fn pack(&mut self, width: u32, height: u32, clock: u64) -> Option<(usize, u32, u32)> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+
+        let shelf = self.allocate(width, height, clock)?;
+        let x = self.shelves[shelf].cursor;
+        self.shelves[shelf].cursor += width;
+        Some((shelf, x, self.shelves[shelf].y))
+    }
+
+    /// Find a shelf with enough space, opening a new one if required.
+    fn allocate(&mut self, width: u32, height: u32, clock: u64) -> Option<usize> {
+        let best = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| shelf.height >= height && shelf.cursor + width <= self.width)
+            .min_by_key(|(_, shelf)| shelf.height)
+            .map(|(i, _)| i);
+
+        // Avoid wasting space by placing small rectangles into tall shelves.
+        if let Some(i) = best {
+            if self.shelves[i].height <= 2 * height {
+                return Some(i);
+            }
+        }
+
+        let next_y = self.shelves.last().map_or(0, |shelf| shelf.y + shelf.height);
+        if next_y + height <= self.height {
+            self.shelves.push(Shelf {
+                y: next_y,
+                height,
+                cursor: 0,
+                last_used: clock,
+            });
+            return Some(self.shelves.len() - 1);
+        }
+
+        best
+    }
+
+    /// Empty the least recently used shelf able to hold a rectangle of `height`.
+    ///
+    /// Returns `None` if no shelf is tall enough.
+    #[cfg_attr(not(feature = "text"), allow(dead_code))]
+    pub fn evict(&mut self, height: u32) -> Option<usize> {
+        let lru = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| shelf.height >= height)
+            .min_by_key(|(_, shelf)| shelf.last_used)
+            .map(|(i, _)| i)?;
+
+        self.shelves[lru].cursor = 0;
+        Some(lru)
+    }
+}
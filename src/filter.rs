@@ -16,6 +16,7 @@ pub use self::radial_box::*;
 pub use self::smoothstep::*;
 
 pub trait Filter {
+    /// Description of the filter including its parameters.
     fn name(&self) -> String;
 
     // Probability distribution function.
@@ -14,7 +14,7 @@ impl BoxFilter {
 }
 
 impl Filter for BoxFilter {
-    fn name(&self) -> String { "Box".into() }
+    fn name(&self) -> String { format!("Box [{}, {}]", self.min, self.max) }
 
     fn pdf(&self, x: f32) -> f32 {
         if x >= self.min && x <= self.max {
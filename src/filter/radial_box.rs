@@ -8,7 +8,7 @@ pub struct RadialBoxFilter {
 }
 
 impl Filter for RadialBoxFilter {
-    fn name(&self) -> String { "RadialBox".into() }
+    fn name(&self) -> String { format!("RadialBox {}", self.radius) }
 
     fn pdf(&self, x: f32) -> f32 {
         todo!()
@@ -12,7 +12,7 @@ impl Smoothstep {
 }
 
 impl Filter for Smoothstep {
-    fn name(&self) -> String { "Smoothstep".into() }
+    fn name(&self) -> String { format!("Smoothstep [{}, {}]", self.e0, self.e1) }
 
     fn pdf(&self, x: f32) -> f32 {
         let t = self.t(x);
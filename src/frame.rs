@@ -1,4 +1,8 @@
-use crate::{Filter, math::*};
+mod lcd;
+
+pub use self::lcd::*;
+
+use crate::{Filter, RelativeBounds, SampleId, SampleJitter, math::*};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Colorspace {
@@ -10,12 +14,16 @@ pub struct Frame {
     pub width: u32,
     pub height: u32,
     pub data: Vec<u32>,
+    /// Reconstructed values of each pixel before `FrameTransform` is applied, rows not flipped.
+    pub coverage: Vec<[f32; 3]>,
 }
 
 pub struct FrameTransform {
     pub colorspace: Colorspace,
     pub flip: bool,
     pub transfer: fn(f32) -> f32,
+    /// Subpixel rendering, only applied by `Frame::reconstruct`.
+    pub lcd: Option<LcdFilter>,
 }
 
 impl Frame {
@@ -24,73 +32,197 @@ impl Frame {
             width,
             height,
             data: vec![0; (width * height) as _],
+            coverage: vec![[0.0; 3]; (width * height) as _],
         }
     }
 
+    /// Filter the samples around each pixel center, or around the subpixel centers if `transform.lcd` is set.
     pub fn reconstruct(&mut self, framebuffer: &Framebuffer, filter: & dyn Filter, transform: &FrameTransform) {
         assert_eq!(self.width, framebuffer.width);
         assert_eq!(self.height, framebuffer.height);
         assert!(framebuffer.is_complete());
 
+        if let Some(lcd) = &transform.lcd {
+            self.reconstruct_lcd(framebuffer, filter, lcd, transform);
+            return;
+        }
+
+        let relative_bounds = filter.relative_bounds((0.5, 0.5));
+        let center = glam::vec2(0.5, 0.5);
+        let scale = glam::vec2(1.0, 1.0);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let coverage = reconstruct_at(framebuffer, filter, &relative_bounds, x, y, center, scale).unwrap_or(0.0);
+                self.store(x, y, coverage, transform);
+            }
+        }
+    }
+
+    /// Reconstruct three subpixels per pixel with a filter narrowed to the subpixel size.
+    ///
+    /// Requires multiple samples per pixel along the stripe direction, pixels without samples
+    /// inside a subpixel footprint fall back to the full pixel coverage.
+    fn reconstruct_lcd(&mut self, framebuffer: &Framebuffer, filter: &dyn Filter, lcd: &LcdFilter, transform: &FrameTransform) {
         let relative_bounds = filter.relative_bounds((0.5, 0.5));
+        let vertical = lcd.layout.is_vertical();
+        let (len, lines) = if vertical {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        let mut subpixels = vec![0.0; 3 * len as usize];
+        for line in 0..lines {
+            for i in 0..len {
+                let (x, y) = if vertical { (line, i) } else { (i, line) };
+                let pixel = reconstruct_at(framebuffer, filter, &relative_bounds, x, y, glam::vec2(0.5, 0.5), glam::vec2(1.0, 1.0)).unwrap_or(0.0);
+
+                for c in 0..3 {
+                    let along = (c as f32 + 0.5) / 3.0;
+                    let (center, scale) = if vertical {
+                        (glam::vec2(0.5, along), glam::vec2(1.0, 1.0 / 3.0))
+                    } else {
+                        (glam::vec2(along, 0.5), glam::vec2(1.0 / 3.0, 1.0))
+                    };
+                    subpixels[3 * i as usize + c] = reconstruct_at(framebuffer, filter, &relative_bounds, x, y, center, scale).unwrap_or(pixel);
+                }
+            }
+
+            let filtered = lcd.apply(&subpixels);
+            for i in 0..len {
+                let (x, y) = if vertical { (line, i) } else { (i, line) };
+                let k = 3 * i as usize;
+                let rgb = lcd.layout.to_rgb([filtered[k], filtered[k + 1], filtered[k + 2]]);
+                self.store_rgb(x, y, rgb, transform);
+            }
+        }
+    }
+
+    /// Hardware style resolve: unweighted average of the samples inside each pixel.
+    pub fn resolve(&mut self, framebuffer: &Framebuffer, transform: &FrameTransform) {
+        assert_eq!(self.width, framebuffer.width);
+        assert_eq!(self.height, framebuffer.height);
+        assert!(framebuffer.is_complete());
 
-        let layer_size = self.width * self.height;
         let num_samples = framebuffer.sample_pos.len();
 
         for y in 0..self.height {
             for x in 0..self.width {
-                let mut acc_sample = 0.0;
-                let mut acc_weight = 0.0;
-
-                let bounds = relative_bounds.offset(x, y, self.width, self.height);
-                for iy in bounds.y.clone() {
-                    for ix in bounds.x.clone() {
-                        let offset = num_samples * (iy * self.width + ix) as usize;
-                        for (sample_id, sample_pos) in framebuffer.sample_pos.iter().enumerate() {
-                            let id = sample_id + offset;
-                            let sample = framebuffer.samples[id];
-
-                            let dx = ix as i32 - x as i32;
-                            let dy = iy as i32 - y as i32;
-                            let weight = filter.pdf(sample_pos.x() - 0.5 + dx as f32) * filter.pdf(sample_pos.y() - 0.5 + dy as f32); // 2d separable filter
-
-                            acc_sample += sample * weight;
-                            acc_weight += weight;
-                        }
-                    }
-                }
+                let offset = num_samples * (y * self.width + x) as usize;
+                let acc_sample: f32 = framebuffer.samples[offset..offset + num_samples].iter().sum();
 
-                let coverage = if acc_weight > 0.0 {
-                    acc_sample / acc_weight
+                let coverage = if num_samples > 0 {
+                    acc_sample / num_samples as f32
                 } else {
                     0.0
                 };
 
-                let coverage = clamp((transform.transfer)(coverage), 0.0, 1.0);
+                self.store(x, y, coverage, transform);
+            }
+        }
+    }
 
-                let opacity = match transform.colorspace {
-                    Colorspace::Linear => coverage,
-                    Colorspace::Srgb => linear_to_srgb(coverage),
-                };
+    /// Fold the reconstructed `frame` of the `pass`-th progressive pass into the running average.
+    ///
+    /// Each pass is reconstructed at its own sample positions before averaging, so the result
+    /// converges for any filter.
+    pub fn accumulate(&mut self, frame: &Frame, pass: usize, transform: &FrameTransform) {
+        assert_eq!(self.width, frame.width);
+        assert_eq!(self.height, frame.height);
 
-                let value = (std::u8::MAX as f64 * opacity as f64) as u32;
-                let i = if transform.flip {
-                    (self.height - y - 1) * self.width + x
-                } else {
-                    y * self.width + x
-                };
-                self.data[i as usize] =
-                    0xFF << 24 | value << 16 | value << 8 | value << 0;
+        let weight = 1.0 / (pass + 1) as f32;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = (y * self.width + x) as usize;
+                let mut rgb = self.coverage[i];
+                for (acc, value) in rgb.iter_mut().zip(&frame.coverage[i]) {
+                    *acc += (value - *acc) * weight;
+                }
+                self.store_rgb(x, y, rgb, transform);
             }
         }
     }
+
+    fn store(&mut self, x: u32, y: u32, coverage: f32, transform: &FrameTransform) {
+        self.store_rgb(x, y, [coverage; 3], transform);
+    }
+
+    fn store_rgb(&mut self, x: u32, y: u32, coverage: [f32; 3], transform: &FrameTransform) {
+        self.coverage[(y * self.width + x) as usize] = coverage;
+        let [r, g, b] = coverage.map(|coverage| {
+            let coverage = clamp((transform.transfer)(coverage), 0.0, 1.0);
+
+            let opacity = match transform.colorspace {
+                Colorspace::Linear => coverage,
+                Colorspace::Srgb => linear_to_srgb(coverage),
+            };
+
+            (u8::MAX as f64 * opacity as f64) as u32
+        });
+
+        let i = if transform.flip {
+            (self.height - y - 1) * self.width + x
+        } else {
+            y * self.width + x
+        };
+        self.data[i as usize] = 0xFF << 24 | r << 16 | g << 8 | b;
+    }
 }
 
+/// Weighted average of the samples around `center` (relative to pixel `(x, y)`).
+///
+/// The filter is stretched by `scale`, `None` if no sample falls inside the footprint.
+fn reconstruct_at(
+    framebuffer: &Framebuffer,
+    filter: &dyn Filter,
+    relative_bounds: &RelativeBounds,
+    x: u32,
+    y: u32,
+    center: glam::Vec2,
+    scale: glam::Vec2,
+) -> Option<f32> {
+    let num_samples = framebuffer.sample_pos.len();
+    let mut acc_sample = 0.0;
+    let mut acc_weight = 0.0;
+
+    let bounds = relative_bounds.offset(x, y, framebuffer.width, framebuffer.height);
+    for iy in bounds.y.clone() {
+        for ix in bounds.x.clone() {
+            let offset = num_samples * (iy * framebuffer.width + ix) as usize;
+            for sample_id in 0..num_samples {
+                let sample_pos = framebuffer.sample_pos(ix, iy, sample_id);
+                let sample = framebuffer.samples[sample_id + offset];
+
+                let dx = ix as i32 - x as i32;
+                let dy = iy as i32 - y as i32;
+                // 2d separable filter
+                let weight = filter.pdf((sample_pos.x() - center.x() + dx as f32) / scale.x())
+                    * filter.pdf((sample_pos.y() - center.y() + dy as f32) / scale.y());
+
+                acc_sample += sample * weight;
+                acc_weight += weight;
+            }
+        }
+    }
+
+    if acc_weight > 0.0 {
+        Some(acc_sample / acc_weight)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone)]
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
     pub sample_pos: Vec<glam::Vec2>,
     pub samples: Vec<f32>,
+    /// Per-pixel variation of `sample_pos`.
+    pub jitter: SampleJitter,
+    /// Position of the first pixel in the jitter pattern, non-zero for regions of a larger target.
+    pub origin: (u32, u32),
 }
 
 impl Framebuffer {
@@ -100,12 +232,15 @@ impl Framebuffer {
             height,
             sample_pos: Vec::new(),
             samples: Vec::new(),
+            jitter: SampleJitter::None,
+            origin: (0, 0),
         }
     }
 
     pub fn reset(&mut self) {
         self.sample_pos.clear();
         self.samples.clear();
+        self.jitter = SampleJitter::None;
     }
 
     pub fn clear(&mut self) {
@@ -118,6 +253,30 @@ impl Framebuffer {
             .extend(&vec![0.0; (self.width * self.height) as _]);
     }
 
+    /// Empty framebuffer covering `width` x `height` pixels starting at `(x, y)`,
+    /// with the same sample positions as the corresponding pixels of `self`.
+    pub fn region(&self, x: u32, y: u32, width: u32, height: u32) -> Framebuffer {
+        let mut region = Framebuffer::new(width, height);
+        for pos in &self.sample_pos {
+            region.add_sample_pos(*pos);
+        }
+        region.jitter = self.jitter.clone();
+        region.origin = (self.origin.0 + x, self.origin.1 + y);
+        region
+    }
+
+    /// Position of a sample inside the pixel at `(x, y)`.
+    pub fn sample_pos(&self, x: u32, y: u32, sample_id: SampleId) -> glam::Vec2 {
+        let pos = self.sample_pos[sample_id];
+        match self.jitter {
+            SampleJitter::None => pos,
+            _ => {
+                let pos = pos + self.jitter.offset(self.origin.0 + x, self.origin.1 + y);
+                glam::vec2(pos.x().fract(), pos.y().fract())
+            }
+        }
+    }
+
     pub fn num_texels(&self) -> usize {
         (self.width * self.height) as _
     }
@@ -126,3 +285,62 @@ impl Framebuffer {
         self.samples.len() == (self.num_texels() * self.sample_pos.len())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoxFilter, Sampler, TentFilter, UniformSampler};
+
+    #[test]
+    fn accumulate_jittered_edge() {
+        let transform = FrameTransform {
+            colorspace: Colorspace::Linear,
+            flip: false,
+            transfer: |x| x,
+            lcd: None,
+        };
+        let sampler = UniformSampler { nx: 1, ny: 1 };
+
+        // Progressive passes of a single sample per pixel, covered right of the edge at x = 1.3.
+        let converge = |filter: &dyn Filter| {
+            let mut framebuffer = Framebuffer::new(4, 1);
+            let mut frame = Frame::new(4, 1);
+            let mut accumulation = Frame::new(4, 1);
+            for pass in 0..256 {
+                framebuffer.reset();
+                sampler.populate_pass(&mut framebuffer, pass);
+                for x in 0..4 {
+                    let pos = x as f32 + framebuffer.sample_pos(x, 0, 0).x();
+                    framebuffer.samples[x as usize] = if pos >= 1.3 { 1.0 } else { 0.0 };
+                }
+                frame.reconstruct(&framebuffer, filter, &transform);
+                accumulation.accumulate(&frame, pass, &transform);
+            }
+            accumulation.coverage.iter().map(|rgb| rgb[0]).collect::<Vec<_>>()
+        };
+
+        let coverage = converge(&BoxFilter::new(-0.5, 0.5));
+        assert_eq!(coverage[0], 0.0);
+        assert!((coverage[1] - 0.7).abs() < 0.02, "{:?}", coverage);
+        assert_eq!(coverage[2..], [1.0, 1.0]);
+
+        // Wider filters converge to the filtered edge instead of blurring the first pass.
+        let coverage = converge(&TentFilter);
+        let analytic = |x: f32| {
+            // Integral of the tent centered at x over [1.3, inf).
+            let d = 1.3 - x;
+            if d <= -1.0 {
+                1.0
+            } else if d <= 0.0 {
+                1.0 - 0.5 * (1.0 + d) * (1.0 + d)
+            } else if d <= 1.0 {
+                0.5 * (1.0 - d) * (1.0 - d)
+            } else {
+                0.0
+            }
+        };
+        for (x, c) in coverage.iter().enumerate() {
+            assert!((c - analytic(x as f32 + 0.5)).abs() < 0.05, "pixel {}: {}", x, c);
+        }
+    }
+}
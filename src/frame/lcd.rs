@@ -0,0 +1,136 @@
+/// Physical order of the color stripes of a LCD panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubpixelLayout {
+    /// Horizontal stripes, red on the left.
+    Rgb,
+    /// Horizontal stripes, blue on the left.
+    Bgr,
+    /// Vertical stacked stripes, red on the top.
+    VerticalRgb,
+    /// Vertical stacked stripes, blue on the top.
+    VerticalBgr,
+}
+
+impl SubpixelLayout {
+    pub fn is_vertical(self) -> bool {
+        matches!(self, SubpixelLayout::VerticalRgb | SubpixelLayout::VerticalBgr)
+    }
+
+    /// Map the subpixel values in physical order to RGB.
+    pub fn to_rgb(self, subpixels: [f32; 3]) -> [f32; 3] {
+        match self {
+            SubpixelLayout::Rgb | SubpixelLayout::VerticalRgb => subpixels,
+            SubpixelLayout::Bgr | SubpixelLayout::VerticalBgr => [subpixels[2], subpixels[1], subpixels[0]],
+        }
+    }
+}
+
+/// Subpixel rendering configuration for `Frame::reconstruct`.
+///
+/// Coverage is reconstructed at the three subpixel centers of each pixel and
+/// smoothed with a 5-tap FIR filter across subpixels to reduce color fringes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LcdFilter {
+    pub layout: SubpixelLayout,
+    pub weights: [f32; 5],
+}
+
+impl LcdFilter {
+    /// FreeType's default filter.
+    pub const DEFAULT_WEIGHTS: [f32; 5] = [
+        0x08 as f32 / 256.0,
+        0x4D as f32 / 256.0,
+        0x56 as f32 / 256.0,
+        0x4D as f32 / 256.0,
+        0x08 as f32 / 256.0,
+    ];
+
+    /// FreeType's light filter, sharper but with more fringing.
+    pub const LIGHT_WEIGHTS: [f32; 5] = [
+        0.0,
+        0x55 as f32 / 256.0,
+        0x56 as f32 / 256.0,
+        0x55 as f32 / 256.0,
+        0.0,
+    ];
+
+    pub fn new(layout: SubpixelLayout) -> Self {
+        LcdFilter {
+            layout,
+            weights: Self::DEFAULT_WEIGHTS,
+        }
+    }
+
+    /// Convolve a line of subpixel values, treating values outside as empty.
+    pub fn apply(&self, subpixels: &[f32]) -> Vec<f32> {
+        (0..subpixels.len())
+            .map(|i| {
+                self.weights
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, w)| {
+                        let j = (i + k).checked_sub(2)?;
+                        subpixels.get(j).map(|s| s * w)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fir_preserves_energy() {
+        let lcd = LcdFilter::new(SubpixelLayout::Rgb);
+
+        let mut impulse = vec![0.0; 9];
+        impulse[4] = 1.0;
+        let response = lcd.apply(&impulse);
+        assert_eq!(response[2..7], lcd.weights);
+        assert!((response.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+
+        // Constant lines remain constant away from the borders.
+        let response = lcd.apply(&[1.0; 9]);
+        assert!(response[2..7].iter().all(|v| (v - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn reconstruct_fringes() {
+        use crate::{BoxFilter, Colorspace, Frame, FrameTransform, Framebuffer, Sampler, UniformSampler};
+
+        let mut framebuffer = Framebuffer::new(9, 1);
+        UniformSampler { nx: 3, ny: 1 }.populate(&mut framebuffer);
+        // Left half covered, edge in the middle of pixel 4.
+        for x in 0..9 {
+            for s in 0..3 {
+                let pos = x as f32 + framebuffer.sample_pos[s].x();
+                framebuffer.samples[s + 3 * x] = if pos < 4.5 { 1.0 } else { 0.0 };
+            }
+        }
+
+        let mut frame = Frame::new(9, 1);
+        let transform = FrameTransform {
+            colorspace: Colorspace::Linear,
+            flip: false,
+            transfer: |x| x,
+            lcd: Some(LcdFilter::new(SubpixelLayout::Rgb)),
+        };
+        frame.reconstruct(&framebuffer, &BoxFilter::new(-0.5, 0.5), &transform);
+
+        let rgb = |pixel: u32| (pixel >> 16 & 0xFF, pixel >> 8 & 0xFF, pixel & 0xFF);
+        let (r, g, b) = rgb(frame.data[1]);
+        assert!(r == g && g == b && r > 250);
+        let (r, _, b) = rgb(frame.data[4]);
+        assert!(r > b);
+        assert_eq!(rgb(frame.data[8]), (0, 0, 0));
+    }
+
+    #[test]
+    fn layout_order() {
+        assert_eq!(SubpixelLayout::Rgb.to_rgb([0.1, 0.2, 0.3]), [0.1, 0.2, 0.3]);
+        assert_eq!(SubpixelLayout::VerticalBgr.to_rgb([0.1, 0.2, 0.3]), [0.3, 0.2, 0.1]);
+    }
+}
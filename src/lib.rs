@@ -1,3 +1,4 @@
+mod atlas;
 mod filter;
 mod frame;
 mod math;
@@ -6,6 +7,10 @@ mod polynomial;
 mod rasterizer;
 mod rect;
 mod sample;
+mod sdf;
+mod svg;
+#[cfg(feature = "text")]
+mod text;
 
 pub use crate::filter::*;
 pub use crate::frame::*;
@@ -14,13 +19,22 @@ pub use crate::polynomial::*;
 pub use crate::rasterizer::*;
 pub use crate::rect::*;
 pub use crate::sample::*;
+pub use crate::sdf::*;
+pub use crate::svg::*;
+#[cfg(feature = "text")]
+pub use crate::text::*;
 pub use minifb::*;
 
 pub type Scene = fn(&mut dyn Rasterizer, &mut Framebuffer);
 
+type RasterizerEntry = (Key, Box<dyn Rasterizer>, Box<dyn Sampler>);
+
 fn transfer_neg_pos(x: f32) -> f32 { x * 0.5 + 0.5 }
 fn transfer_identity(x: f32) -> f32 { x }
 
+/// Number of frames accumulated in progressive mode before going idle.
+const MAX_PROGRESSIVE_PASSES: usize = 256;
+
 pub struct App {
     width: u32,
     height: u32,
@@ -29,19 +43,32 @@ pub struct App {
     window: Window,
     transform: FrameTransform,
 
-    rasterizers: Vec<(Key, Box<dyn Rasterizer>, UniformSampler)>,
+    rasterizers: Vec<RasterizerEntry>,
     scenes: Vec<(Key, Scene)>,
     filters: Vec<(Key, Box<dyn Filter>)>,
 
     active_rasterizer: Option<usize>,
     active_scene: Option<usize>,
     active_filter: Option<usize>,
+
+    /// Resolve by averaging the pixel samples, ignoring the active filter.
+    hardware_resolve: bool,
+
+    /// Refine the frame over successive passes while idle.
+    progressive: bool,
+    /// Running average of the reconstructed passes.
+    accumulation: Frame,
+    num_passes: usize,
+
+    /// Draw commands of the last rendered pass, used for picking.
+    commands: Vec<DrawCommand>,
 }
 
 impl App {
     pub fn new(width: u32, height: u32, scale: Scale) -> Self {
         let frame = Frame::new(width, height);
         let framebuffer = Framebuffer::new(width, height);
+        let accumulation = Frame::new(width, height);
 
         let mut window = Window::new(
             "furu",
@@ -69,6 +96,7 @@ impl App {
                 colorspace: Colorspace::Srgb,
                 flip: false,
                 transfer: |x: f32| 0.5 * x + 0.5,
+                lcd: None,
             },
             rasterizers: Vec::new(),
             active_rasterizer: None,
@@ -76,15 +104,20 @@ impl App {
             active_scene: None,
             filters: Vec::new(),
             active_filter: None,
+            hardware_resolve: false,
+            progressive: false,
+            accumulation,
+            num_passes: 0,
+            commands: Vec::new(),
         }
     }
 
-    pub fn add_rasterizer<R: Rasterizer + 'static>(&mut self, key: Key, rasterizer: R, sampler: UniformSampler) {
+    pub fn add_rasterizer<R: Rasterizer + 'static, S: Sampler + 'static>(&mut self, key: Key, rasterizer: R, sampler: S) {
         if self.active_rasterizer.is_none() {
             self.active_rasterizer = Some(self.rasterizers.len());
         }
 
-        self.rasterizers.push((key, Box::new(rasterizer), sampler));
+        self.rasterizers.push((key, Box::new(rasterizer), Box::new(sampler)));
     }
 
     pub fn add_scene(&mut self, key: Key, scene: Scene) {
@@ -103,27 +136,57 @@ impl App {
         self.filters.push((key, Box::new(filter)));
     }
 
+    fn displayed_frame(&self) -> &Frame {
+        if self.progressive {
+            &self.accumulation
+        } else {
+            &self.frame
+        }
+    }
+
     fn update_frame(&mut self) {
+        self.num_passes = 0;
+        self.render_pass();
+    }
+
+    fn render_pass(&mut self) {
         match (self.active_rasterizer, self.active_scene, self.active_filter) {
             (Some(rasterizer_id), Some(scene_id), Some(filter_id)) => {
                 let (_, rasterizer, sampler) = &mut self.rasterizers[rasterizer_id];
                 let scene = &mut self.scenes[scene_id].1;
                 let filter = &self.filters[filter_id].1;
+                let pass = self.num_passes;
 
                 let start = std::time::Instant::now();
                 self.framebuffer.reset();
-                sampler.populate(&mut self.framebuffer);
+                sampler.populate_pass(&mut self.framebuffer, pass);
 
                 print!("render scene..");
-                scene(&mut **rasterizer, &mut self.framebuffer);
+                let mut recorder = DrawRecorder::new(&mut **rasterizer, self.width, self.height);
+                scene(&mut recorder, &mut self.framebuffer);
+                self.commands = recorder.finish();
                 println!("{:?}", start.elapsed());
 
-                print!("reconstruct frame..");
-                self.frame
-                    .reconstruct(&mut self.framebuffer, &**filter, &self.transform);
+                if self.hardware_resolve {
+                    print!("resolve frame..");
+                    self.frame.resolve(&self.framebuffer, &self.transform);
+                } else {
+                    print!("reconstruct frame..");
+                    self.frame
+                        .reconstruct(&self.framebuffer, &**filter, &self.transform);
+                }
+                if self.progressive {
+                    self.accumulation.accumulate(&self.frame, pass, &self.transform);
+                }
                 println!("{:?}", start.elapsed());
 
-                self.window.set_title(&format!("{} - Scene {}", rasterizer.name(), scene_id));
+                self.num_passes += 1;
+
+                if self.progressive {
+                    self.window.set_title(&format!("{} - Scene {} - Pass {}", rasterizer.name(), scene_id, self.num_passes));
+                } else {
+                    self.window.set_title(&format!("{} - Scene {}", rasterizer.name(), scene_id));
+                }
             }
             _ => (),
         }
@@ -184,11 +247,42 @@ impl App {
                                 self.transform.transfer = transfer_identity;
                                 update_frame = true;
                             },
+                            Key::H => {
+                                self.hardware_resolve = !self.hardware_resolve;
+                                update_frame = true;
+                            },
+                            Key::G => {
+                                self.progressive = !self.progressive;
+                                update_frame = true;
+                            },
+                            Key::L => {
+                                // Cycle subpixel layouts
+                                self.transform.lcd = match self.transform.lcd.map(|lcd| lcd.layout) {
+                                    None => Some(LcdFilter::new(SubpixelLayout::Rgb)),
+                                    Some(SubpixelLayout::Rgb) => Some(LcdFilter::new(SubpixelLayout::Bgr)),
+                                    Some(SubpixelLayout::Bgr) => Some(LcdFilter::new(SubpixelLayout::VerticalRgb)),
+                                    Some(SubpixelLayout::VerticalRgb) => Some(LcdFilter::new(SubpixelLayout::VerticalBgr)),
+                                    Some(SubpixelLayout::VerticalBgr) => None,
+                                };
+                                update_frame = true;
+                            },
                             Key::P => {
                                 if let Some(pos) = self.window.get_mouse_pos(minifb::MouseMode::Discard) {
                                     let y = pos.1 as usize;
                                     let x = pos.0 as usize;
-                                    println!("pos: {:?} {:?}", pos, (self.frame.data[y * self.width as usize + x] & 0xFF));
+                                    println!("pos: {:?} {:?}", pos, (self.displayed_frame().data[y * self.width as usize + x] & 0xFF));
+
+                                    // Commands are recorded in framebuffer coordinates, undo the vertical flip of the frame.
+                                    let point = if self.transform.flip {
+                                        glam::vec2(pos.0, self.height as f32 - pos.1)
+                                    } else {
+                                        glam::vec2(pos.0, pos.1)
+                                    };
+                                    let (width, height) = (self.width, self.height);
+                                    match topmost_command(&self.commands, point, |command| !command.is_clear(width, height)) {
+                                        Some(i) => println!("hit: command {} of {}", i, self.commands.len()),
+                                        None => println!("hit: none"),
+                                    }
                                 }
                             },
                             _ => {}
@@ -200,8 +294,14 @@ impl App {
                     }
                 });
 
+            // Progressive refinement while idle
+            if self.progressive && self.num_passes < MAX_PROGRESSIVE_PASSES {
+                self.render_pass();
+            }
+
+            let frame = if self.progressive { &self.accumulation } else { &self.frame };
             self.window
-                .update_with_buffer(&self.frame.data, self.width as _, self.height as _)
+                .update_with_buffer(&frame.data, self.width as _, self.height as _)
                 .unwrap();
         }
     }
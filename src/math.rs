@@ -39,4 +39,14 @@ pub fn linear_to_srgb(value: f32) -> f32 {
     } else {
         1.055 * value.powf(5.0 / 12.0) - 0.055
     }
-}
\ No newline at end of file
+}
+
+/// Inverse of `linear_to_srgb`, decodes sRGB encoded values in `[0, 1]`.
+#[cfg_attr(not(any(feature = "svg", feature = "text")), allow(dead_code))]
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -1,5 +1,37 @@
+mod arc;
+mod boolean;
+mod flatten;
+mod hit;
+mod offset;
+mod orientation;
+mod path;
+mod shapes;
+mod validate;
+
+pub use self::boolean::*;
+pub use self::hit::*;
+pub use self::offset::*;
+pub use self::orientation::*;
+pub use self::path::*;
+pub use self::validate::*;
+
+use crate::{Extent, Offset, Rect};
+
 pub type Segment = Vec<Curve>;
 
+/// Abscissas and weights of the 8-point Gauss-Legendre rule (symmetric half).
+const GAUSS_LEGENDRE: [(f32, f32); 4] = [
+    (0.183_434_64, 0.362_683_78),
+    (0.525_532_4, 0.313_706_65),
+    (0.796_666_5, 0.222_381_03),
+    (0.960_289_86, 0.101_228_54),
+];
+/// Relative error at which the adaptive arc length quadrature stops refining.
+const ARC_LENGTH_TOLERANCE: f32 = 1e-6;
+const ARC_LENGTH_MAX_DEPTH: u32 = 8;
+/// Relative threshold for parameters and collinearity when monotonizing.
+const MONOTONIZE_EPSILON: f32 = 1e-5;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Curve {
     Line {
@@ -13,6 +45,17 @@ pub enum Curve {
     },
 }
 
+/// Rule deciding which regions enclosed by a path are filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+/// Axis aligned bounding box.
+///
+/// `Aabb::empty()` is the identity of `union` and contains no points.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Aabb {
     pub min: glam::Vec2,
     pub max: glam::Vec2,
@@ -26,6 +69,30 @@ impl Aabb {
         }
     }
 
+    pub fn empty() -> Self {
+        Aabb {
+            min: glam::Vec2::new(f32::INFINITY, f32::INFINITY),
+            max: glam::Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_point(p: glam::Vec2) -> Self {
+        Aabb { min: p, max: p }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y()
+    }
+
+    /// Size of the box, zero for empty boxes.
+    pub fn extent(&self) -> glam::Vec2 {
+        if self.is_empty() {
+            glam::vec2(0.0, 0.0)
+        } else {
+            self.max - self.min
+        }
+    }
+
     pub fn union(&self, other: &Aabb) -> Aabb {
         Aabb {
             min: glam::Vec2::new(
@@ -39,14 +106,64 @@ impl Aabb {
         }
     }
 
+    /// Overlapping region, empty if the boxes are disjoint.
+    pub fn intersect(&self, other: &Aabb) -> Aabb {
+        let aabb = Aabb {
+            min: glam::Vec2::new(
+                self.min.x().max(other.min.x()),
+                self.min.y().max(other.min.y()),
+            ),
+            max: glam::Vec2::new(
+                self.max.x().min(other.max.x()),
+                self.max.y().min(other.max.y()),
+            ),
+        };
+        if aabb.is_empty() {
+            Aabb::empty()
+        } else {
+            aabb
+        }
+    }
+
+    /// Check if the point lies inside or on the border of the box.
+    pub fn contains(&self, p: glam::Vec2) -> bool {
+        self.min.x() <= p.x() && p.x() <= self.max.x() && self.min.y() <= p.y() && p.y() <= self.max.y()
+    }
+
+    /// Smallest box containing both the box and the point.
+    pub fn include(&self, p: glam::Vec2) -> Aabb {
+        self.union(&Aabb::from_point(p))
+    }
+
+    /// Grow the box by `margin` on each side.
+    pub fn expand(&self, margin: glam::Vec2) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        Aabb {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// Rect mapping the box onto the local area starting at `offset_local`.
+    pub fn to_rect(&self, offset_local: Offset, extent_local: Extent) -> Rect {
+        Rect {
+            offset_local,
+            extent_local,
+            offset_curve: if self.is_empty() { glam::vec2(0.0, 0.0) } else { self.min },
+            extent_curve: self.extent(),
+        }
+    }
+
     pub fn from_curves(curves: &[Curve]) -> Self {
         curves
             .iter()
-            .fold(Aabb::zero(), |aabb, curve| aabb.union(&curve.aabb()))
+            .fold(Aabb::empty(), |aabb, curve| aabb.union(&curve.aabb()))
     }
 
     pub fn from_segments(segments: &[Segment]) -> Self {
-        segments.iter().fold(Aabb::zero(), |aabb, segment| {
+        segments.iter().fold(Aabb::empty(), |aabb, segment| {
             aabb.union(&Aabb::from_curves(&segment))
         })
     }
@@ -60,17 +177,18 @@ impl Curve {
                 max: glam::Vec2::new(p0.x().max(p1.x()), p0.y().max(p1.y())),
             },
             Curve::Quad { p0, p1, p2 } => {
-                // bad approx
-                Aabb {
-                    min: glam::Vec2::new(
-                        p0.x().min(p1.x()).min(p2.x()),
-                        p0.y().min(p1.y()).min(p2.y()),
-                    ),
-                    max: glam::Vec2::new(
-                        p0.x().max(p1.x()).max(p2.x()),
-                        p0.y().max(p1.y()).max(p2.y()),
-                    ),
+                // Extrema at the roots of the derivative `2 * ((p1 - p0) + t * (p0 - 2p1 + p2))`.
+                let a = p0 - 2.0 * p1 + p2;
+                let mut aabb = Aabb::from_point(p0).include(p2);
+                for &(d0, d) in &[(p1.x() - p0.x(), a.x()), (p1.y() - p0.y(), a.y())] {
+                    if d != 0.0 {
+                        let t = -d0 / d;
+                        if 0.0 < t && t < 1.0 {
+                            aabb = aabb.include(self.eval(t));
+                        }
+                    }
                 }
+                aabb
             }
         }
     }
@@ -82,107 +200,530 @@ impl Curve {
         }
     }
 
-    pub fn monotonize(&self) -> Vec<Curve> {
+    pub fn start(&self) -> glam::Vec2 {
         match *self {
-            Curve::Line { .. } => vec![*self],
-            Curve::Quad { p0, p1, p2 } => {
-                let min = glam::vec2(p0.x().min(p2.x()), p0.y().min(p2.y()));
-                let max = glam::vec2(p0.x().max(p2.x()), p0.y().max(p2.y()));
+            Curve::Line { p0, .. } | Curve::Quad { p0, .. } => p0,
+        }
+    }
 
-                let tx = if p1.x() < min.x() || max.x() < p1.x() {
-                    Some((p0.x() - p1.x()) / (p0.x() - 2.0 * p1.x() + p2.x()))
-                } else {
-                    None
-                };
+    pub fn end(&self) -> glam::Vec2 {
+        match *self {
+            Curve::Line { p1, .. } => p1,
+            Curve::Quad { p2, .. } => p2,
+        }
+    }
 
-                let ty = if p1.y() < min.y() || max.y() < p1.y() {
-                    Some((p0.y() - p1.y()) / (p0.y() - 2.0 * p1.y() + p2.y()))
-                } else {
-                    None
-                };
-
-                match (tx, ty) {
-                    (Some(tx), Some(ty)) => {
-                        let t = tx.min(ty);
-                        let p = self.eval(t);
-                        let p10 = Curve::Line { p0, p1 }.eval(t);
-                        let p11 = Curve::Line { p0: p1, p1: p2 }.eval(t);
-                        let mut curves = vec![Curve::Quad { p0, p1: p10, p2: p }];
-                        curves.extend(Curve::Quad { p0: p, p1: p11, p2 }.monotonize());
-                        curves
-                    }
-                    (Some(t), None) | (None, Some(t)) => {
-                        let p = self.eval(t);
-                        let p10 = Curve::Line { p0, p1 }.eval(t);
-                        let p11 = Curve::Line { p0: p1, p1: p2 }.eval(t);
-                        vec![Curve::Quad { p0, p1: p10, p2: p }, Curve::Quad { p0: p, p1: p11, p2 }]
-                    }
-                    (None, None) => vec![*self],
+    /// Tangent vector `dp/dt`, not normalized.
+    pub fn derivative(&self, t: f32) -> glam::Vec2 {
+        match *self {
+            Curve::Line { p0, p1 } => p1 - p0,
+            Curve::Quad { p0, p1, p2 } => 2.0 * (1.0 - t) * (p1 - p0) + 2.0 * t * (p2 - p1),
+        }
+    }
+
+    /// Unit normal `(-dy, dx)` of the tangent, pointing outside for oriented contours.
+    ///
+    /// Falls back to the chord direction where the derivative vanishes, zero for curves collapsed to a point.
+    pub fn normal(&self, t: f32) -> glam::Vec2 {
+        let mut d = self.derivative(t);
+        if d.length_squared() == 0.0 {
+            d = self.end() - self.start();
+        }
+        if d.length_squared() == 0.0 {
+            return glam::vec2(0.0, 0.0);
+        }
+        glam::vec2(-d.y(), d.x()).normalize()
+    }
+
+    /// Split into the parts before and after `t` (de Casteljau).
+    pub fn split_at(&self, t: f32) -> (Curve, Curve) {
+        match *self {
+            Curve::Line { p0, p1 } => {
+                let m = self.eval(t);
+                (Curve::Line { p0, p1: m }, Curve::Line { p0: m, p1 })
+            }
+            Curve::Quad { p0, p1, p2 } => {
+                let a = p0 + (p1 - p0) * t;
+                let b = p1 + (p2 - p1) * t;
+                let m = a + (b - a) * t;
+                (Curve::Quad { p0, p1: a, p2: m }, Curve::Quad { p0: m, p1: b, p2 })
+            }
+        }
+    }
+
+    /// Part of the curve between `t0` and `t1`, reversed if `t1 < t0`.
+    pub fn subsegment(&self, t0: f32, t1: f32) -> Curve {
+        match *self {
+            Curve::Line { .. } => Curve::Line {
+                p0: self.eval(t0),
+                p1: self.eval(t1),
+            },
+            Curve::Quad { p0, p1, p2 } => {
+                Curve::Quad {
+                    p0: self.eval(t0),
+                    p1: quad_polar(p0, p1, p2, t0, t1),
+                    p2: self.eval(t1),
                 }
             }
         }
     }
 
+    /// Arc length of the whole curve.
+    pub fn length(&self) -> f32 {
+        self.arc_length(0.0, 1.0)
+    }
+
+    /// Arc length between the parameters `t0` and `t1`.
+    pub fn arc_length(&self, t0: f32, t1: f32) -> f32 {
+        match *self {
+            Curve::Line { p0, p1 } => (p1 - p0).length() * (t1 - t0),
+            Curve::Quad { .. } => {
+                let estimate = self.gauss_legendre(t0, t1);
+                self.arc_length_adaptive(t0, t1, estimate, ARC_LENGTH_MAX_DEPTH)
+            }
+        }
+    }
+
+    /// Refine the quadrature by halving the interval until both halves agree with the estimate.
+    fn arc_length_adaptive(&self, t0: f32, t1: f32, estimate: f32, depth: u32) -> f32 {
+        let tm = 0.5 * (t0 + t1);
+        let left = self.gauss_legendre(t0, tm);
+        let right = self.gauss_legendre(tm, t1);
+        if depth == 0 || (left + right - estimate).abs() <= ARC_LENGTH_TOLERANCE * estimate.abs() {
+            left + right
+        } else {
+            self.arc_length_adaptive(t0, tm, left, depth - 1) + self.arc_length_adaptive(tm, t1, right, depth - 1)
+        }
+    }
+
+    fn gauss_legendre(&self, t0: f32, t1: f32) -> f32 {
+        let half = 0.5 * (t1 - t0);
+        let center = 0.5 * (t1 + t0);
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|&(x, w)| {
+                w * (self.derivative(center - half * x).length() + self.derivative(center + half * x).length())
+            })
+            .sum::<f32>()
+            * half
+    }
+
+    /// Parameter at which the arc length from the start reaches `s`.
+    ///
+    /// `s` is clamped to the curve length.
+    pub fn t_at_length(&self, s: f32) -> f32 {
+        let length = self.length();
+        if length <= 0.0 || s <= 0.0 {
+            return 0.0;
+        }
+        if s >= length {
+            return 1.0;
+        }
+
+        if let Curve::Line { .. } = self {
+            return s / length;
+        }
+
+        // Newton iteration safeguarded by bisection.
+        let (mut lo, mut hi) = (0.0, 1.0);
+        let mut t = s / length;
+        for _ in 0..16 {
+            let err = self.arc_length(0.0, t) - s;
+            if err.abs() <= 1e-5 * length {
+                break;
+            }
+            if err > 0.0 {
+                hi = t;
+            } else {
+                lo = t;
+            }
+            let speed = self.derivative(t).length();
+            let next = t - err / speed;
+            t = if speed > 0.0 && lo < next && next < hi {
+                next
+            } else {
+                0.5 * (lo + hi)
+            };
+        }
+        t
+    }
+
+    /// Split into pieces monotone in x and y.
+    ///
+    /// Quads with (nearly) collinear control points become lines, zero length pieces are dropped.
+    pub fn monotonize(&self) -> Vec<Curve> {
+        let (p0, p1, p2) = match *self {
+            Curve::Line { p0, p1 } => return if p0 == p1 { Vec::new() } else { vec![*self] },
+            Curve::Quad { p0, p1, p2 } => (p0, p1, p2),
+        };
+
+        // Parameters of the extrema, only present if the control point lies outside of the endpoints.
+        let extremum = |p0: f32, p1: f32, p2: f32| {
+            if p1 < p0.min(p2) || p0.max(p2) < p1 {
+                let t = (p0 - p1) / (p0 - 2.0 * p1 + p2);
+                Some(t).filter(|t| MONOTONIZE_EPSILON < *t && *t < 1.0 - MONOTONIZE_EPSILON)
+            } else {
+                None
+            }
+        };
+
+        let mut splits = [extremum(p0.x(), p1.x(), p2.x()), extremum(p0.y(), p1.y(), p2.y())]
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        splits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        splits.dedup_by(|b, a| *b - *a <= MONOTONIZE_EPSILON);
+
+        let mut curves = Vec::with_capacity(splits.len() + 1);
+        let mut t0 = 0.0;
+        let mut start = p0;
+        for t1 in splits.into_iter().chain(Some(1.0)) {
+            let end = if t1 == 1.0 { p2 } else { self.eval(t1) };
+            curves.extend(Curve::monotone_quad(start, quad_polar(p0, p1, p2, t0, t1), end));
+            t0 = t1;
+            start = end;
+        }
+        curves
+    }
+
+    /// Quad with the control point clamped to the endpoint box, collapsed to a line if degenerate.
+    fn monotone_quad(p0: glam::Vec2, p1: glam::Vec2, p2: glam::Vec2) -> Option<Curve> {
+        if p0 == p2 {
+            return None;
+        }
+
+        let p1 = p1.max(p0.min(p2)).min(p0.max(p2));
+        let chord = p2 - p0;
+        let deviation = chord.perp_dot(p1 - p0).abs();
+        if deviation <= MONOTONIZE_EPSILON * chord.length_squared() {
+            Some(Curve::Line { p0, p1: p2 })
+        } else {
+            Some(Curve::Quad { p0, p1, p2 })
+        }
+    }
+
+    /// Same curve traversed in opposite direction.
+    pub fn reversed(&self) -> Curve {
+        match *self {
+            Curve::Line { p0, p1 } => Curve::Line { p0: p1, p1: p0 },
+            Curve::Quad { p0, p1, p2 } => Curve::Quad { p0: p2, p1, p2: p0 },
+        }
+    }
+
+    /// Signed area enclosed between the curve and the origin.
+    ///
+    /// Summed over a closed contour, the rasterizers fill contours with negative area (y down).
+    pub fn signed_area(&self) -> f32 {
+        let cross = |a: glam::Vec2, b: glam::Vec2| a.x() * b.y() - a.y() * b.x();
+        match *self {
+            Curve::Line { p0, p1 } => 0.5 * cross(p0, p1),
+            Curve::Quad { p0, p1, p2 } => (2.0 * cross(p0, p1) + 2.0 * cross(p1, p2) + cross(p0, p2)) / 6.0,
+        }
+    }
+
+    /// Flip all contours if the path as a whole is oriented opposite to the rasterizer convention.
+    ///
+    /// The relative orientation of the contours (e.g. holes) is preserved.
+    pub fn orient_segments(segments: &[Segment]) -> Vec<Segment> {
+        let area: f32 = segments.iter().flatten().map(Curve::signed_area).sum();
+        if area > 0.0 {
+            segments.iter().map(|segment| Curve::reverse_contour(segment)).collect()
+        } else {
+            segments.to_vec()
+        }
+    }
+
     pub fn monotize_path(curves: &[Curve]) -> Vec<Curve> {
         curves.iter().map(|curve| curve.monotonize()).flatten().collect()
     }
 }
 
+/// Polar form (blossom) of a quad, the control point of the part between `t0` and `t1`.
+fn quad_polar(p0: glam::Vec2, p1: glam::Vec2, p2: glam::Vec2, t0: f32, t1: f32) -> glam::Vec2 {
+    (1.0 - t0) * (1.0 - t1) * p0 + ((1.0 - t0) * t1 + t0 * (1.0 - t1)) * p1 + t0 * t1 * p2
+}
+
 pub struct PathBuilder {
-    curves: Vec<Curve>,
-    first: glam::Vec2,
-    last: glam::Vec2,
+    path: Path,
+    tolerance: f32,
 }
 
 impl PathBuilder {
     pub fn new() -> Self {
         PathBuilder {
-            curves: Vec::new(),
-            first: glam::vec2(0.0, 0.0),
-            last: glam::vec2(0.0, 0.0),
+            path: Path::new(),
+            tolerance: 0.1,
         }
     }
 
+    /// Maximal deviation (in curve space) when approximating curves by quads.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
     pub fn move_to(mut self, p: glam::Vec2) -> Self {
-        self.first = p;
-        self.last = p;
+        self.path.move_to(p);
         self
     }
 
     pub fn line_to(mut self, p: glam::Vec2) -> Self {
-        self.curves.push(Curve::Line {
-            p0: self.last,
-            p1: p,
-        });
-        self.last = p;
+        self.path.line_to(p);
         self
     }
 
     pub fn quad_to(mut self, p1: glam::Vec2, p2: glam::Vec2) -> Self {
-        self.curves.push(Curve::Quad {
-            p0: self.last,
-            p1,
-            p2,
-        });
-        self.last = p2;
+        self.path.quad_to(p1, p2);
+        self
+    }
+
+    /// Cubic bezier approximated by quads within the path tolerance.
+    ///
+    /// The cubic is subdivided uniformly, the number of pieces is derived from the
+    /// error bound `sqrt(3)/36 * |p3 - 3p2 + 3p1 - p0|` of the mid-point approximation.
+    pub fn cubic_to(mut self, p1: glam::Vec2, p2: glam::Vec2, p3: glam::Vec2) -> Self {
+        let p0 = self.path.current().unwrap_or_else(|| glam::vec2(0.0, 0.0));
+
+        let err = (p3 - 3.0 * p2 + 3.0 * p1 - p0).length() * 3.0f32.sqrt() / 36.0;
+        let n = ((err / self.tolerance).cbrt().ceil() as usize).max(1);
+
+        let eval = |t: f32| {
+            let s = 1.0 - t;
+            s * s * s * p0 + 3.0 * s * s * t * p1 + 3.0 * s * t * t * p2 + t * t * t * p3
+        };
+        let derivative = |t: f32| {
+            let s = 1.0 - t;
+            3.0 * s * s * (p1 - p0) + 6.0 * s * t * (p2 - p1) + 3.0 * t * t * (p3 - p2)
+        };
+
+        for i in 0..n {
+            let t0 = i as f32 / n as f32;
+            let t1 = (i + 1) as f32 / n as f32;
+            let dt = (t1 - t0) / 3.0;
+
+            let c0 = eval(t0);
+            let c3 = if i + 1 == n { p3 } else { eval(t1) };
+            let c1 = c0 + derivative(t0) * dt;
+            let c2 = c3 - derivative(t1) * dt;
+
+            self = self.quad_to((3.0 * (c1 + c2) - c0 - c3) / 4.0, c3);
+        }
+
         self
     }
 
+    /// Connect back to the start of the current subpath.
     pub fn close(mut self) -> Self {
-        self.curves.push(Curve::Line {
-            p0: self.last,
-            p1: self.first,
-        });
-        self.last = self.first;
+        self.path.close();
         self
     }
 
     pub fn monotonize(mut self) -> Self {
-        self.curves = Curve::monotize_path(&self.curves);
+        self.path = self.path.map_contours(Curve::monotize_path);
         self
     }
 
+    /// Replace all curves by lines within `tolerance`.
+    ///
+    /// Cubics are already approximated by quads, the total error is bounded by the sum of both tolerances.
+    pub fn flatten(mut self, tolerance: f32) -> Self {
+        self.path = self.path.map_contours(|curves| Curve::flatten_path(curves, tolerance));
+        self
+    }
+
+    /// All curves of all subpaths.
     pub fn finish(self) -> Vec<Curve> {
-        self.curves
+        self.path.curves().collect()
+    }
+
+    pub fn finish_path(self) -> Path {
+        self.path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quad_aabb() {
+        let quad = Curve::Quad {
+            p0: glam::vec2(10.0, 10.0),
+            p1: glam::vec2(20.0, 30.0),
+            p2: glam::vec2(30.0, 10.0),
+        };
+        let aabb = quad.aabb();
+        assert_eq!(aabb.min, glam::vec2(10.0, 10.0));
+        // Apex at t = 0.5, half way to the control point.
+        assert_eq!(aabb.max, glam::vec2(30.0, 20.0));
+    }
+
+    #[test]
+    fn aabb_accumulation() {
+        let curves = PathBuilder::new()
+            .move_to(glam::vec2(10.0, 20.0))
+            .line_to(glam::vec2(30.0, 25.0))
+            .close()
+            .finish();
+        let aabb = Aabb::from_curves(&curves);
+        assert_eq!(aabb.min, glam::vec2(10.0, 20.0));
+        assert_eq!(aabb.max, glam::vec2(30.0, 25.0));
+
+        assert!(Aabb::from_segments(&[]).is_empty());
+        assert!(aabb.contains(glam::vec2(10.0, 25.0)));
+        assert!(!aabb.contains(glam::vec2(0.0, 0.0)));
+
+        let other = Aabb::from_point(glam::vec2(20.0, 0.0)).include(glam::vec2(40.0, 22.0));
+        let overlap = aabb.intersect(&other);
+        assert_eq!((overlap.min, overlap.max), (glam::vec2(20.0, 20.0), glam::vec2(30.0, 22.0)));
+        assert!(aabb.intersect(&Aabb::from_point(glam::vec2(0.0, 0.0))).is_empty());
+
+        let rect = aabb.expand(glam::vec2(1.0, 1.0)).to_rect(glam::vec2(0.0, 0.0), glam::vec2(22.0, 7.0));
+        assert_eq!(rect.local_to_curve(glam::vec2(0.0, 0.0)), glam::vec2(9.0, 19.0));
+        assert_eq!(rect.local_to_curve(glam::vec2(22.0, 7.0)), glam::vec2(31.0, 26.0));
+    }
+
+    #[test]
+    fn split_and_subsegment() {
+        let quad = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(50.0, 100.0),
+            p2: glam::vec2(100.0, 0.0),
+        };
+        let close = |a: glam::Vec2, b: glam::Vec2| (a - b).length() < 1e-4;
+
+        let (a, b) = quad.split_at(0.25);
+        assert!(close(a.eval(0.5), quad.eval(0.125)));
+        assert!(close(b.eval(0.0), quad.eval(0.25)));
+        assert!(close(b.eval(1.0 / 3.0), quad.eval(0.5)));
+
+        let sub = quad.subsegment(0.2, 0.6);
+        for i in 0..=4 {
+            let t = i as f32 / 4.0;
+            assert!(close(sub.eval(t), quad.eval(0.2 + 0.4 * t)));
+        }
+        assert!(close(quad.subsegment(0.6, 0.2).eval(0.0), quad.eval(0.6)));
+
+        assert!(close(quad.normal(0.5), glam::vec2(0.0, 1.0)));
+        assert!(close(quad.derivative(0.0), glam::vec2(100.0, 200.0)));
+    }
+
+    #[test]
+    fn arc_length() {
+        let quad = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(50.0, 100.0),
+            p2: glam::vec2(100.0, 0.0),
+        };
+
+        let n = 10_000;
+        let polyline: f32 = (0..n)
+            .map(|i| (quad.eval((i + 1) as f32 / n as f32) - quad.eval(i as f32 / n as f32)).length())
+            .sum();
+        assert!((quad.length() - polyline).abs() < 1e-2);
+
+        let line = Curve::Line {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(30.0, 40.0),
+        };
+        assert_eq!(line.length(), 50.0);
+        assert_eq!(line.t_at_length(10.0), 0.2);
+
+        for &s in &[0.0, 10.0, 60.0, 120.0, quad.length()] {
+            let t = quad.t_at_length(s);
+            assert!((quad.arc_length(0.0, t) - s).abs() < 1e-2);
+        }
+        assert_eq!(quad.t_at_length(1000.0), 1.0);
+
+        // Cusp-like quad with vanishing derivative.
+        let cusp = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(100.0, 0.0),
+            p2: glam::vec2(0.0, 0.0),
+        };
+        assert!((cusp.length() - 100.0).abs() < 1e-2);
+    }
+
+    /// Deterministic pseudo random coordinates in `[-100, 100]`, rounded to produce degenerate cases.
+    fn random_points(seed: u32, n: usize) -> Vec<glam::Vec2> {
+        let mut state = seed.wrapping_mul(0x9e37_79b9) | 1;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % 2001) as f32 / 10.0 - 100.0
+        };
+        (0..n)
+            .map(|i| {
+                let p = glam::vec2(next(), next());
+                if i % 3 == 0 {
+                    glam::vec2(p.x().round(), (p.y() / 25.0).round() * 25.0)
+                } else {
+                    p
+                }
+            })
+            .collect()
+    }
+
+    fn is_monotone(curve: &Curve) -> bool {
+        let between = |a: f32, b: f32, c: f32| a.min(c) <= b && b <= a.max(c);
+        match *curve {
+            Curve::Line { .. } => true,
+            Curve::Quad { p0, p1, p2 } => between(p0.x(), p1.x(), p2.x()) && between(p0.y(), p1.y(), p2.y()),
+        }
+    }
+
+    #[test]
+    fn monotonize_properties() {
+        let mut cases = Vec::new();
+        for seed in 0..2000 {
+            let p = random_points(seed, 3);
+            cases.push(Curve::Quad { p0: p[0], p1: p[1], p2: p[2] });
+        }
+        let (a, b) = (glam::vec2(10.0, 10.0), glam::vec2(50.0, 30.0));
+        cases.extend(&[
+            // Control point on an end point, collinear and overshooting, collapsed to a point.
+            Curve::Quad { p0: a, p1: a, p2: b },
+            Curve::Quad { p0: a, p1: b, p2: b },
+            Curve::Quad { p0: a, p1: 0.5 * (a + b), p2: b },
+            Curve::Quad { p0: a, p1: 2.0 * b - a, p2: b },
+            Curve::Quad { p0: a, p1: b, p2: a },
+            Curve::Quad { p0: a, p1: a, p2: a },
+            Curve::Line { p0: a, p1: a },
+        ]);
+
+        for curve in &cases {
+            let pieces = curve.monotonize();
+            for piece in &pieces {
+                assert!(is_monotone(piece), "{:?} -> {:?}", curve, pieces);
+                assert!(piece.start() != piece.end(), "{:?} -> {:?}", curve, pieces);
+            }
+            assert!(pieces.windows(2).all(|pair| pair[0].end() == pair[1].start()));
+
+            if let (Some(first), Some(last)) = (pieces.first(), pieces.last()) {
+                assert_eq!(first.start(), curve.start());
+                assert_eq!(last.end(), curve.end());
+            }
+
+            // Pieces trace the same curve.
+            let bounds = curve.aabb();
+            let scale = 1.0 + bounds.extent().length();
+            for piece in &pieces {
+                let mid = piece.eval(0.5);
+                let distance = (0..=2000)
+                    .map(|i| (curve.eval(i as f32 / 2000.0) - mid).length())
+                    .fold(f32::MAX, f32::min);
+                assert!(distance < 1e-3 * scale, "{:?} -> {:?}", curve, pieces);
+            }
+        }
+
+        // Degenerate input collapses.
+        assert!(Curve::Quad { p0: a, p1: a, p2: a }.monotonize().is_empty());
+        assert!(matches!(
+            Curve::Quad { p0: a, p1: 2.0 * b - a, p2: b }.monotonize()[..],
+            [Curve::Line { .. }, Curve::Line { .. }]
+        ));
+        assert_eq!(Curve::Quad { p0: a, p1: b, p2: a }.monotonize().len(), 2);
     }
 }
@@ -0,0 +1,148 @@
+//! Elliptical arcs approximated by quads, shared by SVG path data, shapes and round joins.
+
+use crate::{math::clamp, PathBuilder};
+use std::f32::consts::PI;
+
+/// Upper bound on the number of quads per arc.
+const ARC_MAX_QUADS: usize = 1024;
+
+/// Quads approximating an elliptical arc within `tolerance`, as control and end points.
+///
+/// The arc starts at angle `start` and sweeps by `sweep` radians, positive angles turning from +x towards +y.
+/// Each quad spans at most a quarter turn with its control point at the intersection of the end tangents.
+pub(crate) fn arc_quads(
+    center: glam::Vec2,
+    radii: glam::Vec2,
+    rotation: f32,
+    start: f32,
+    sweep: f32,
+    tolerance: f32,
+) -> Vec<(glam::Vec2, glam::Vec2)> {
+    let (sin_phi, cos_phi) = rotation.sin_cos();
+    let point = |angle: f32, scale: f32| {
+        let (sin, cos) = angle.sin_cos();
+        let v = glam::vec2(radii.x() * cos, radii.y() * sin) * scale;
+        center + glam::vec2(cos_phi * v.x() - sin_phi * v.y(), sin_phi * v.x() + cos_phi * v.y())
+    };
+
+    // Radial error of a single quad spanning `2 * phi` on a circle: r * (1 - cos phi)² / (2 cos phi)
+    let r = radii.x().abs().max(radii.y().abs());
+    let mut n = (sweep.abs() / (0.5 * PI)).ceil().max(1.0) as usize;
+    loop {
+        let phi = 0.5 * sweep.abs() / n as f32;
+        let err = r * (1.0 - phi.cos()).powi(2) / (2.0 * phi.cos());
+        if err <= tolerance || n >= ARC_MAX_QUADS {
+            break;
+        }
+        n += 1;
+    }
+
+    let step = sweep / n as f32;
+    (0..n)
+        .map(|i| {
+            let a0 = start + step * i as f32;
+            (point(a0 + 0.5 * step, 1.0 / (0.5 * step).cos()), point(a0 + step, 1.0))
+        })
+        .collect()
+}
+
+impl PathBuilder {
+    /// Elliptical arc from the current point to `to` with SVG semantics, `rotation` in degrees.
+    ///
+    /// Conversion to center parameterization follows the SVG implementation notes (F.6.5).
+    /// Radii too small to reach `to` are scaled up, zero radii result in a line.
+    pub fn arc_to(mut self, radii: glam::Vec2, rotation: f32, large_arc: bool, sweep: bool, to: glam::Vec2) -> Self {
+        let from = self.path.current().unwrap_or_else(|| glam::vec2(0.0, 0.0));
+        let mut rx = radii.x().abs();
+        let mut ry = radii.y().abs();
+
+        if from == to {
+            return self;
+        }
+        if rx == 0.0 || ry == 0.0 {
+            return self.line_to(to);
+        }
+
+        let (sin_phi, cos_phi) = rotation.to_radians().sin_cos();
+        let rotate = |v: glam::Vec2| glam::vec2(cos_phi * v.x() - sin_phi * v.y(), sin_phi * v.x() + cos_phi * v.y());
+        let rotate_inv = |v: glam::Vec2| glam::vec2(cos_phi * v.x() + sin_phi * v.y(), -sin_phi * v.x() + cos_phi * v.y());
+
+        let p = rotate_inv((from - to) * 0.5);
+
+        // Scale up radii if the endpoints can't be reached.
+        let lambda = (p.x() * p.x()) / (rx * rx) + (p.y() * p.y()) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let num = rx * rx * ry * ry - rx * rx * p.y() * p.y() - ry * ry * p.x() * p.x();
+        let den = rx * rx * p.y() * p.y() + ry * ry * p.x() * p.x();
+        let mut coeff = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            coeff = -coeff;
+        }
+        let center_p = glam::vec2(coeff * rx * p.y() / ry, -coeff * ry * p.x() / rx);
+        let center = rotate(center_p) + (from + to) * 0.5;
+
+        let angle = |u: glam::Vec2, v: glam::Vec2| {
+            let sign = if u.x() * v.y() - u.y() * v.x() < 0.0 { -1.0 } else { 1.0 };
+            sign * clamp(u.dot(v) / (u.length() * v.length()), -1.0, 1.0).acos()
+        };
+
+        let u = glam::vec2((p.x() - center_p.x()) / rx, (p.y() - center_p.y()) / ry);
+        let v = glam::vec2((-p.x() - center_p.x()) / rx, (-p.y() - center_p.y()) / ry);
+        let theta = angle(glam::vec2(1.0, 0.0), u);
+        let mut delta = angle(u, v) % (2.0 * PI);
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        let quads = arc_quads(center, glam::vec2(rx, ry), rotation.to_radians(), theta, delta, self.tolerance);
+        let n = quads.len();
+        for (i, (ctrl, p2)) in quads.into_iter().enumerate() {
+            self = self.quad_to(ctrl, if i + 1 == n { to } else { p2 });
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Curve;
+
+    #[test]
+    fn arcs() {
+        let tolerance = 0.01;
+        let arc = PathBuilder::new()
+            .with_tolerance(tolerance)
+            .move_to(glam::vec2(0.0, 0.0))
+            .arc_to(glam::vec2(10.0, 10.0), 0.0, false, true, glam::vec2(20.0, 0.0))
+            .finish();
+        for curve in &arc {
+            for i in 0..=8 {
+                let r = (curve.eval(i as f32 / 8.0) - glam::vec2(10.0, 0.0)).length();
+                assert!((r - 10.0).abs() <= tolerance);
+            }
+        }
+        // Positive angle direction is clockwise on screen, passing above the chord.
+        assert!(crate::Aabb::from_curves(&arc).min.y() < -9.9);
+        assert_eq!(arc[arc.len() - 1].end(), glam::vec2(20.0, 0.0));
+
+        // Three quarters of the circle instead of one.
+        let length = |large_arc| {
+            PathBuilder::new()
+                .move_to(glam::vec2(0.0, 0.0))
+                .arc_to(glam::vec2(10.0, 10.0), 0.0, large_arc, true, glam::vec2(10.0, 10.0))
+                .finish()
+                .iter()
+                .map(Curve::length)
+                .sum::<f32>()
+        };
+        assert!((length(false) - 5.0 * PI).abs() < 0.5);
+        assert!((length(true) - 15.0 * PI).abs() < 0.5);
+    }
+}
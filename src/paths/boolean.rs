@@ -0,0 +1,414 @@
+//! Boolean operations on filled paths.
+//!
+//! All curves are split at their mutual intersections, the resulting edges are kept if
+//! the operation result is filled on exactly one side and linked into new contours.
+
+use crate::{hit_test, math::clamp, Aabb, Curve, FillRule, NormalizeOptions, Path, Segment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathOp {
+    Union,
+    Intersection,
+    /// First path minus the second one.
+    Difference,
+    Xor,
+}
+
+impl PathOp {
+    fn contains(self, a: bool, b: bool) -> bool {
+        match self {
+            PathOp::Union => a || b,
+            PathOp::Intersection => a && b,
+            PathOp::Difference => a && !b,
+            PathOp::Xor => a != b,
+        }
+    }
+}
+
+/// Subdivision depth limit of the curve intersection.
+const MAX_DEPTH: u32 = 24;
+/// Tolerances relative to the size of the input.
+const INTERSECT_EPSILON: f32 = 1e-6;
+const WELD_EPSILON: f32 = 1e-5;
+const SIDE_EPSILON: f32 = 1e-4;
+
+/// Maximal distance of the curve to its chord is below `eps`.
+fn is_flat(curve: &Curve, eps: f32) -> bool {
+    match *curve {
+        Curve::Line { .. } => true,
+        Curve::Quad { p0, p1, p2 } => {
+            let chord = p2 - p0;
+            (chord.perp_dot(p1 - p0)).abs() <= eps * chord.length().max(eps)
+        }
+    }
+}
+
+/// Intersection parameters of two line segments, including the end points of collinear overlaps.
+fn intersect_lines(a0: glam::Vec2, a1: glam::Vec2, b0: glam::Vec2, b1: glam::Vec2, eps: f32, out: &mut Vec<(f32, f32)>) {
+    let r = a1 - a0;
+    let s = b1 - b0;
+    let qp = b0 - a0;
+    let denom = r.perp_dot(s);
+    let (len_r, len_s) = (r.length(), s.length());
+    if len_r == 0.0 || len_s == 0.0 {
+        return;
+    }
+
+    if denom.abs() > eps * len_r.max(len_s) * 1e-3 {
+        let t = qp.perp_dot(s) / denom;
+        let u = qp.perp_dot(r) / denom;
+        let (slack_t, slack_u) = (eps / len_r, eps / len_s);
+        if -slack_t <= t && t <= 1.0 + slack_t && -slack_u <= u && u <= 1.0 + slack_u {
+            out.push((clamp(t, 0.0, 1.0), clamp(u, 0.0, 1.0)));
+        }
+    } else if qp.perp_dot(r).abs() <= eps * len_r {
+        // Collinear, split both lines at the end points of the other one.
+        let project_a = |p: glam::Vec2| (p - a0).dot(r) / (len_r * len_r);
+        let project_b = |p: glam::Vec2| (p - b0).dot(s) / (len_s * len_s);
+        for &(t, u) in &[(project_a(b0), 0.0), (project_a(b1), 1.0), (0.0, project_b(a0)), (1.0, project_b(a1))] {
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                out.push((t, u));
+            }
+        }
+    }
+}
+
+/// Intersection parameters of two curves by recursive subdivision.
+#[allow(clippy::too_many_arguments)]
+fn intersect_curves(
+    a: &Curve,
+    (a_t0, a_t1): (f32, f32),
+    b: &Curve,
+    (b_t0, b_t1): (f32, f32),
+    eps: f32,
+    depth: u32,
+    out: &mut Vec<(f32, f32)>,
+) {
+    if a.aabb().expand(glam::vec2(eps, eps)).intersect(&b.aabb()).is_empty() {
+        return;
+    }
+
+    if depth == 0 || (is_flat(a, eps) && is_flat(b, eps)) {
+        let mut local = Vec::new();
+        intersect_lines(a.start(), a.end(), b.start(), b.end(), eps, &mut local);
+        out.extend(
+            local
+                .iter()
+                .map(|(t, u)| (a_t0 + (a_t1 - a_t0) * t, b_t0 + (b_t1 - b_t0) * u)),
+        );
+        return;
+    }
+
+    let a_tm = 0.5 * (a_t0 + a_t1);
+    let b_tm = 0.5 * (b_t0 + b_t1);
+    let halves = |curve: &Curve, t0, tm, t1| {
+        if is_flat(curve, eps) {
+            vec![(*curve, (t0, t1))]
+        } else {
+            let (c0, c1) = curve.split_at(0.5);
+            vec![(c0, (t0, tm)), (c1, (tm, t1))]
+        }
+    };
+    for (a, ra) in halves(a, a_t0, a_tm, a_t1) {
+        for (b, rb) in halves(b, b_t0, b_tm, b_t1) {
+            intersect_curves(&a, ra, &b, rb, eps, depth - 1, out);
+        }
+    }
+}
+
+/// Same geometry, possibly traversed in opposite direction.
+fn is_same_curve(a: &Curve, b: &Curve) -> bool {
+    match (*a, *b) {
+        (Curve::Line { p0, p1 }, Curve::Line { p0: q0, p1: q1 }) => (p0 == q0 && p1 == q1) || (p0 == q1 && p1 == q0),
+        (Curve::Quad { p0, p1, p2 }, Curve::Quad { p0: q0, p1: q1, p2: q2 }) => {
+            p1 == q1 && ((p0 == q0 && p2 == q2) || (p0 == q2 && p2 == q0))
+        }
+        _ => false,
+    }
+}
+
+/// Replace points by the first previously seen point within `eps`.
+struct Welder {
+    vertices: Vec<glam::Vec2>,
+    eps: f32,
+}
+
+impl Welder {
+    fn weld(&mut self, p: glam::Vec2) -> usize {
+        match self.vertices.iter().position(|v| (*v - p).length() <= self.eps) {
+            Some(i) => i,
+            None => {
+                self.vertices.push(p);
+                self.vertices.len() - 1
+            }
+        }
+    }
+}
+
+struct Edge {
+    start: usize,
+    end: usize,
+    curve: Curve,
+}
+
+/// Close contours and split curves into monotonic pieces, as expected by `resolve_regions`.
+pub(crate) fn prepare_segments(segments: &[Segment]) -> Vec<Segment> {
+    let options = NormalizeOptions {
+        merge_collinear: false,
+        tolerance: 0.0,
+        ..NormalizeOptions::default()
+    };
+    Curve::normalize_segments(segments, &options)
+        .iter()
+        .map(|segment| Curve::monotize_path(segment))
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Combine two filled paths, interpreting both with `fill_rule`.
+///
+/// The result consists of non-overlapping counter-clockwise contours with clockwise holes.
+pub fn path_boolean(a: &[Segment], b: &[Segment], op: PathOp, fill_rule: FillRule) -> Vec<Segment> {
+    let (a, b) = (prepare_segments(a), prepare_segments(b));
+    let curves = a.iter().chain(&b).flatten().cloned().collect::<Vec<_>>();
+    resolve_regions(&curves, |p| op.contains(hit_test(&a, p, fill_rule), hit_test(&b, p, fill_rule)))
+}
+
+/// Outline of the region where `inside` holds, bounded by (parts of) `curves`.
+///
+/// Curves are split at their mutual intersections and kept where `inside` differs on both sides.
+pub(crate) fn resolve_regions<F: Fn(glam::Vec2) -> bool>(curves: &[Curve], inside: F) -> Vec<Segment> {
+    let bounds = Aabb::from_curves(curves);
+    if bounds.is_empty() {
+        return Vec::new();
+    }
+    let scale = bounds.extent().x().max(bounds.extent().y()).max(1e-3);
+    let weld_eps = WELD_EPSILON * scale;
+
+    // Split parameters of all curves, with the intersection points shared between both curves.
+    let mut splits = vec![Vec::new(); curves.len()];
+    let aabbs = curves.iter().map(Curve::aabb).collect::<Vec<_>>();
+    for i in 0..curves.len() {
+        for j in i + 1..curves.len() {
+            if aabbs[i].expand(glam::vec2(weld_eps, weld_eps)).intersect(&aabbs[j]).is_empty()
+                || is_same_curve(&curves[i], &curves[j])
+            {
+                continue;
+            }
+
+            let mut params = Vec::new();
+            let eps = INTERSECT_EPSILON * scale;
+            intersect_curves(&curves[i], (0.0, 1.0), &curves[j], (0.0, 1.0), eps, MAX_DEPTH, &mut params);
+            for (t, u) in params {
+                let p = 0.5 * (curves[i].eval(t) + curves[j].eval(u));
+                splits[i].push((t, p));
+                splits[j].push((u, p));
+            }
+        }
+    }
+
+    let mut welder = Welder {
+        vertices: Vec::new(),
+        eps: weld_eps,
+    };
+    let mut edges = Vec::new();
+    for (curve, mut splits) in curves.iter().zip(splits) {
+        splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut t0 = 0.0;
+        let mut start = curve.start();
+        for (t1, end) in splits.into_iter().chain(Some((1.0, curve.end()))) {
+            if (end - start).length() <= weld_eps && t1 < 1.0 {
+                continue;
+            }
+            let piece = curve.subsegment(t0, t1);
+            let (s, e) = (welder.weld(start), welder.weld(end));
+            if s != e {
+                let curve = match piece {
+                    Curve::Line { .. } => Curve::Line {
+                        p0: welder.vertices[s],
+                        p1: welder.vertices[e],
+                    },
+                    Curve::Quad { p1, .. } => Curve::Quad {
+                        p0: welder.vertices[s],
+                        p1,
+                        p2: welder.vertices[e],
+                    },
+                };
+                edges.push(Edge { start: s, end: e, curve });
+            }
+            t0 = t1;
+            start = end;
+        }
+    }
+
+    // Keep edges with the result filled on one side only, oriented counter-clockwise around the filled side.
+    let side = SIDE_EPSILON * scale;
+    let mut kept: Vec<Edge> = Vec::new();
+    for edge in edges {
+        let mid = edge.curve.eval(0.5);
+        let normal = edge.curve.normal(0.5);
+        let (outer, inner) = (inside(mid + normal * side), inside(mid - normal * side));
+        if outer == inner {
+            continue;
+        }
+
+        let edge = if inner {
+            edge
+        } else {
+            Edge {
+                start: edge.end,
+                end: edge.start,
+                curve: edge.curve.reversed(),
+            }
+        };
+
+        // Coincident edges of both inputs.
+        let duplicate = kept.iter().any(|other| {
+            other.start == edge.start
+                && other.end == edge.end
+                && (other.curve.eval(0.5) - edge.curve.eval(0.5)).length() <= weld_eps
+        });
+        if !duplicate {
+            kept.push(edge);
+        }
+    }
+
+    // Link edges into closed contours.
+    let mut used = vec![false; kept.len()];
+    let mut contours = Vec::new();
+    for first in 0..kept.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut contour = vec![kept[first].curve];
+        let mut current = kept[first].end;
+        while current != kept[first].start {
+            match (0..kept.len()).find(|&i| !used[i] && kept[i].start == current) {
+                Some(next) => {
+                    used[next] = true;
+                    contour.push(kept[next].curve);
+                    current = kept[next].end;
+                }
+                None => break,
+            }
+        }
+        contours.push(contour);
+    }
+
+    Curve::normalize_segments(&contours, &NormalizeOptions::default())
+}
+
+impl Path {
+    /// See `path_boolean`.
+    pub fn boolean(&self, other: &Path, op: PathOp, fill_rule: FillRule) -> Path {
+        Path::from_segments(&path_boolean(&self.to_segments(), &other.to_segments(), op, fill_rule))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PathBuilder;
+
+    fn square(x: f32, y: f32, size: f32) -> Segment {
+        PathBuilder::new()
+            .move_to(glam::vec2(x, y))
+            .line_to(glam::vec2(x, y + size))
+            .line_to(glam::vec2(x + size, y + size))
+            .line_to(glam::vec2(x + size, y))
+            .close()
+            .finish()
+    }
+
+    fn circle(center: glam::Vec2, r: f32) -> Segment {
+        let mut path = PathBuilder::new().move_to(center + glam::vec2(r, 0.0));
+        let n = 8;
+        let step = 2.0 * std::f32::consts::PI / n as f32;
+        for i in 0..n {
+            let angle = |k: f32| glam::vec2((k * step).cos(), (k * step).sin());
+            let mid = angle(i as f32 + 0.5) * (r / (0.5 * step).cos());
+            path = path.quad_to(center + mid, center + angle((i + 1) as f32) * r);
+        }
+        Curve::reverse_contour(&path.finish())
+    }
+
+    /// Check the result against the operation applied to the inputs on a grid of points.
+    fn check(a: &[Segment], b: &[Segment], op: PathOp) -> Vec<Segment> {
+        let result = path_boolean(a, b, op, FillRule::NonZero);
+        assert_eq!(Curve::validate_segments(&result), Ok(()));
+
+        let bounds = Aabb::from_segments(a).union(&Aabb::from_segments(b)).expand(glam::vec2(1.0, 1.0));
+        let n = 37;
+        for i in 0..=n {
+            for j in 0..=n {
+                let p = bounds.min + bounds.extent() * glam::vec2(i as f32 + 0.31, j as f32 + 0.17) / (n + 1) as f32;
+                let expected = op.contains(hit_test(a, p, FillRule::NonZero), hit_test(b, p, FillRule::NonZero));
+                // Non-overlapping result: winding is 0 or 1 everywhere.
+                let winding = Curve::winding_number(&result, p);
+                assert_eq!(winding, expected as i32, "{:?} at {:?}", op, p);
+            }
+        }
+        result
+    }
+
+    fn area(segments: &[Segment]) -> f32 {
+        -segments.iter().map(|segment| Curve::contour_area(segment)).sum::<f32>()
+    }
+
+    #[test]
+    fn squares() {
+        let a = vec![square(0.0, 0.0, 10.0)];
+        let b = vec![square(5.0, 5.0, 10.0)];
+
+        assert!((area(&check(&a, &b, PathOp::Union)) - 175.0).abs() < 1e-3);
+        assert!((area(&check(&a, &b, PathOp::Intersection)) - 25.0).abs() < 1e-3);
+        assert!((area(&check(&a, &b, PathOp::Difference)) - 75.0).abs() < 1e-3);
+        assert!((area(&check(&a, &b, PathOp::Xor)) - 150.0).abs() < 1e-3);
+
+        // Collinear merged edges: the union of two squares sharing an edge is a rectangle.
+        let union = check(&a, &[square(10.0, 0.0, 10.0)], PathOp::Union);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 4);
+
+        // Hole cut into the square, input drawn in any direction.
+        let hole = check(&a, &[Curve::reverse_contour(&square(2.0, 2.0, 4.0))], PathOp::Difference);
+        assert_eq!(hole.len(), 2);
+        assert!((area(&hole) - 84.0).abs() < 1e-3);
+
+        assert!(check(&a, &[square(20.0, 20.0, 5.0)], PathOp::Intersection).is_empty());
+
+        // Coincident and partially overlapping edges.
+        let same = check(&a, &a, PathOp::Union);
+        assert_eq!(same.len(), 1);
+        assert!((area(&same) - 100.0).abs() < 1e-3);
+        assert!(check(&a, &a, PathOp::Xor).is_empty());
+        assert!((area(&check(&a, &[square(10.0, 5.0, 10.0)], PathOp::Union)) - 200.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn curves() {
+        let a = vec![circle(glam::vec2(10.0, 10.0), 8.0)];
+        let b = vec![circle(glam::vec2(18.0, 12.0), 6.0), square(0.0, 14.0, 6.0)];
+
+        for &op in &[PathOp::Union, PathOp::Intersection, PathOp::Difference, PathOp::Xor] {
+            check(&a, &b, op);
+        }
+
+        let union = check(&a, &b, PathOp::Union);
+        let intersection = check(&a, &b, PathOp::Intersection);
+        assert!((area(&union) + area(&intersection) - area(&a) - area(&check(&b, &[], PathOp::Union))).abs() < 1e-2);
+
+        let path = PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(0.0, 10.0))
+            .line_to(glam::vec2(10.0, 10.0))
+            .line_to(glam::vec2(10.0, 0.0))
+            .close()
+            .finish_path();
+        let clip = Path::from_segments(&a);
+        let result = path.boolean(&clip, PathOp::Intersection, FillRule::NonZero);
+        assert_eq!(result.validate(), Ok(()));
+    }
+}
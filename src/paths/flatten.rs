@@ -0,0 +1,174 @@
+//! Adaptive flattening of curves into polylines.
+//!
+//! Quads are subdivided by mapping them onto the basic parabola `y = x^2` and
+//! distributing the points evenly along the integral of its curvature-based error
+//! metric (R. Levien, "Flattening quadratic Béziers").
+
+use crate::{Curve, Rect, Segment};
+
+/// Approximation of `∫ (1 + 4x²)^-0.25 dx`.
+fn approx_parabola_integral(x: f32) -> f32 {
+    const D: f32 = 0.67;
+    x / (1.0 - D + (D.powi(4) + 0.25 * x * x).sqrt().sqrt())
+}
+
+/// Approximate inverse of `approx_parabola_integral`.
+fn approx_parabola_inv_integral(x: f32) -> f32 {
+    const B: f32 = 0.39;
+    x * (1.0 - B + (B * B + 0.25 * x * x).sqrt())
+}
+
+impl Curve {
+    /// Approximate the curve by lines deviating at most `tolerance` from it.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Curve> {
+        let (p0, p1, p2) = match *self {
+            Curve::Line { .. } => return vec![*self],
+            Curve::Quad { p0, p1, p2 } => (p0, p1, p2),
+        };
+
+        let d01 = p1 - p0;
+        let d12 = p2 - p1;
+        let dd = d01 - d12;
+        let cross = (p2 - p0).perp_dot(dd);
+        let x0 = d01.dot(dd) / cross;
+        let x2 = d12.dot(dd) / cross;
+        let scale = (cross / (dd.length() * (x2 - x0))).abs();
+
+        if !scale.is_finite() || scale == 0.0 {
+            // (Nearly) collinear control points, the extrema are the only vertices needed.
+            return self
+                .monotonize()
+                .iter()
+                .map(|curve| Curve::Line {
+                    p0: curve.start(),
+                    p1: curve.end(),
+                })
+                .collect();
+        }
+
+        let sqrt_tolerance = tolerance.sqrt();
+        let a0 = approx_parabola_integral(x0);
+        let a2 = approx_parabola_integral(x2);
+        let da = (a2 - a0).abs();
+        let sqrt_scale = scale.sqrt();
+        let val = if x0.signum() == x2.signum() {
+            da * sqrt_scale
+        } else {
+            // The cusp of the parabola lies inside, the error is bounded by the tolerance there.
+            let xmin = sqrt_tolerance / sqrt_scale;
+            sqrt_tolerance * da / approx_parabola_integral(xmin)
+        };
+
+        let n = ((0.5 * val / sqrt_tolerance).ceil() as usize).max(1);
+        let u0 = approx_parabola_inv_integral(a0);
+        let u2 = approx_parabola_inv_integral(a2);
+
+        let mut lines = Vec::with_capacity(n);
+        let mut last = p0;
+        for i in 1..n {
+            let a = a0 + (a2 - a0) * (i as f32 / n as f32);
+            let t = (approx_parabola_inv_integral(a) - u0) / (u2 - u0);
+            let p = self.eval(t);
+            lines.push(Curve::Line { p0: last, p1: p });
+            last = p;
+        }
+        lines.push(Curve::Line { p0: last, p1: p2 });
+        lines
+    }
+
+    /// Flatten all curves with `tolerance` in curve space.
+    pub fn flatten_path(curves: &[Curve], tolerance: f32) -> Vec<Curve> {
+        curves.iter().flat_map(|curve| curve.flatten(tolerance)).collect()
+    }
+
+    /// Flatten all segments drawn with `rect` with `tolerance` in pixels.
+    pub fn flatten_segments(segments: &[Segment], rect: &Rect, tolerance: f32) -> Vec<Segment> {
+        // Curve space units per pixel along the axis with the highest magnification.
+        let dxdy = rect.curve_dxdy();
+        let tolerance = tolerance * dxdy.x().abs().min(dxdy.y().abs());
+        segments
+            .iter()
+            .map(|segment| Curve::flatten_path(segment, tolerance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::clamp;
+
+    /// Largest distance of points on the curve to the polyline.
+    fn max_deviation(curve: &Curve, lines: &[Curve]) -> f32 {
+        (0..=1000)
+            .map(|i| {
+                let p = curve.eval(i as f32 / 1000.0);
+                lines
+                    .iter()
+                    .map(|line| {
+                        let (a, b) = (line.start(), line.end());
+                        let t = clamp((p - a).dot(b - a) / (b - a).length_squared(), 0.0, 1.0);
+                        (a + (b - a) * t - p).length()
+                    })
+                    .fold(f32::MAX, f32::min)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn flatten_within_tolerance() {
+        let quads = [
+            Curve::Quad {
+                p0: glam::vec2(0.0, 0.0),
+                p1: glam::vec2(50.0, 100.0),
+                p2: glam::vec2(100.0, 0.0),
+            },
+            // Sharp turn around the control point.
+            Curve::Quad {
+                p0: glam::vec2(0.0, 0.0),
+                p1: glam::vec2(100.0, 100.0),
+                p2: glam::vec2(1.0, 0.0),
+            },
+            // Collinear, overshooting the end point.
+            Curve::Quad {
+                p0: glam::vec2(0.0, 0.0),
+                p1: glam::vec2(100.0, 0.0),
+                p2: glam::vec2(50.0, 0.0),
+            },
+        ];
+
+        for quad in &quads {
+            for &tolerance in &[1.0, 0.1, 0.01] {
+                let lines = quad.flatten(tolerance);
+                assert!(lines.iter().all(|line| matches!(line, Curve::Line { .. })));
+                assert_eq!(lines[0].start(), quad.start());
+                assert_eq!(lines.last().unwrap().end(), quad.end());
+                assert!(lines.windows(2).all(|pair| pair[0].end() == pair[1].start()));
+                assert!(max_deviation(quad, &lines) <= tolerance * 1.05);
+            }
+        }
+
+        // Uniform subdivision bounds the error by `|p0 - 2p1 + p2| / (4n²)`, flat parts need fewer points.
+        let uniform = (200.0f32 / (4.0 * 0.1)).sqrt().ceil() as usize;
+        assert!(quads[0].flatten(0.1).len() < uniform);
+    }
+
+    #[test]
+    fn flatten_in_pixel_space() {
+        let segments = vec![vec![Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(0.5, 1.0),
+            p2: glam::vec2(1.0, 0.0),
+        }]];
+        let rect = Rect {
+            offset_local: glam::vec2(0.0, 0.0),
+            extent_local: glam::vec2(100.0, 200.0),
+            offset_curve: glam::vec2(0.0, 0.0),
+            extent_curve: glam::vec2(1.0, 1.0),
+        };
+
+        let flat = Curve::flatten_segments(&segments, &rect, 0.25);
+        assert!(flat[0].len() > 1);
+        assert!(max_deviation(&segments[0][0], &flat[0]) * 200.0 <= 0.25 * 1.05);
+    }
+}
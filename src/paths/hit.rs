@@ -0,0 +1,97 @@
+//! Point queries against filled and stroked paths.
+
+use crate::{closest_quadratic, math::clamp, Curve, FillRule, Segment};
+
+impl Curve {
+    /// Unsigned distance from `p` to the closest point on the curve.
+    pub fn distance(&self, p: glam::Vec2) -> f32 {
+        let line = |p0: glam::Vec2, p1: glam::Vec2| {
+            let dir = p1 - p0;
+            let len_sq = dir.length_squared();
+            let t = if len_sq > 0.0 {
+                (p - p0).dot(dir) / len_sq
+            } else {
+                0.0
+            };
+            (p0 + dir * clamp(t, 0.0, 1.0) - p).length()
+        };
+
+        match *self {
+            Curve::Line { p0, p1 } => line(p0, p1),
+            Curve::Quad { p0, p1, p2 } => {
+                // Quads with a vanishing second derivative are lines from `p0` to `p2`.
+                if (p2 - 2.0 * p1 + p0).length_squared() <= 1e-12 * (p2 - p0).length_squared() {
+                    return line(p0, p2);
+                }
+
+                let t = closest_quadratic(p, p0, p1, p2);
+                [t.x(), t.y(), t.z(), 0.0, 1.0]
+                    .iter()
+                    .filter(|t| t.is_finite())
+                    .map(|t| (self.eval(clamp(*t, 0.0, 1.0)) - p).length())
+                    .fold(f32::MAX, f32::min)
+            }
+        }
+    }
+}
+
+/// Check if `point` lies inside the filled path.
+pub fn hit_test(path: &[Segment], point: glam::Vec2, fill_rule: FillRule) -> bool {
+    let winding = Curve::winding_number(path, point);
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Check if `point` lies on the path stroked with `width`, accepting points up to `tolerance` outside.
+pub fn hit_test_stroke(path: &[Segment], point: glam::Vec2, width: f32, tolerance: f32) -> bool {
+    let max_distance = 0.5 * width + tolerance;
+    path.iter().flatten().any(|curve| curve.distance(point) <= max_distance)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PathBuilder;
+
+    #[test]
+    fn fill_and_stroke() {
+        // Square with a hole drawn in the same direction.
+        let square = |offset: f32, size: f32| {
+            PathBuilder::new()
+                .move_to(glam::vec2(offset, offset))
+                .line_to(glam::vec2(offset, offset + size))
+                .line_to(glam::vec2(offset + size, offset + size))
+                .line_to(glam::vec2(offset + size, offset))
+                .close()
+                .finish()
+        };
+        let path = vec![square(0.0, 10.0), square(3.0, 4.0)];
+
+        assert!(hit_test(&path, glam::vec2(1.0, 1.0), FillRule::NonZero));
+        assert!(hit_test(&path, glam::vec2(5.0, 5.0), FillRule::NonZero));
+        assert!(!hit_test(&path, glam::vec2(5.0, 5.0), FillRule::EvenOdd));
+        assert!(!hit_test(&path, glam::vec2(11.0, 5.0), FillRule::NonZero));
+
+        assert!(hit_test_stroke(&path, glam::vec2(10.5, 5.0), 1.0, 0.0));
+        assert!(!hit_test_stroke(&path, glam::vec2(11.0, 5.0), 1.0, 0.25));
+        assert!(hit_test_stroke(&path, glam::vec2(11.0, 5.0), 1.0, 0.5));
+        assert!(!hit_test_stroke(&path, glam::vec2(1.5, 1.5), 1.0, 0.5));
+
+        let arch = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(5.0, 10.0),
+            p2: glam::vec2(10.0, 0.0),
+        };
+        assert!((arch.distance(glam::vec2(5.0, 8.0)) - 3.0).abs() < 1e-4);
+        assert!((arch.distance(glam::vec2(-3.0, -4.0)) - 5.0).abs() < 1e-4);
+
+        let straight = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(5.0, 0.0),
+            p2: glam::vec2(10.0, 0.0),
+        };
+        assert_eq!(straight.distance(glam::vec2(4.0, 2.0)), 2.0);
+    }
+}
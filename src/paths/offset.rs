@@ -0,0 +1,290 @@
+//! Growing and shrinking closed outlines by a signed distance.
+
+use super::arc::arc_quads;
+use crate::{prepare_segments, resolve_regions, Curve, NormalizeOptions, Path, Segment};
+
+/// Shape of the offset outline at corners between curves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    /// Circular arc around the corner.
+    Round,
+    /// Sharp corner, beveled if the tip is further than `limit` times the distance from the corner
+    /// (same as SVG `stroke-miterlimit`).
+    Miter { limit: f32 },
+    /// Straight line cutting off the corner.
+    Bevel,
+}
+
+/// Subdivision depth limit when approximating the offset of a quad.
+const OFFSET_MAX_DEPTH: u32 = 8;
+
+impl Curve {
+    /// Curve moved by `distance` along its normal, approximated by quads within `tolerance`.
+    pub fn offset(&self, distance: f32, tolerance: f32) -> Vec<Curve> {
+        let mut curves = Vec::new();
+        for curve in self.monotonize() {
+            offset_monotone(&curve, distance, tolerance, OFFSET_MAX_DEPTH, &mut curves);
+        }
+        curves
+    }
+
+    /// Grow (positive `distance`) or shrink (negative `distance`) filled contours.
+    ///
+    /// Contours are expected in fill orientation, counter-clockwise outlines with clockwise holes
+    /// (see `Curve::fix_orientation`); open contours are closed. The result consists of
+    /// non-overlapping contours like `path_boolean`, quads are approximated within `tolerance`.
+    pub fn offset_segments(segments: &[Segment], distance: f32, join: Join, tolerance: f32) -> Vec<Segment> {
+        let segments = Curve::normalize_segments(segments, &NormalizeOptions::default());
+        if distance == 0.0 {
+            return segments;
+        }
+
+        let raw = segments
+            .iter()
+            .map(|segment| offset_contour(segment, distance, join, tolerance))
+            .collect::<Vec<_>>();
+        let raw = prepare_segments(&raw);
+        let curves = raw.iter().flatten().cloned().collect::<Vec<_>>();
+
+        // Overlaps at concave corners wind twice around the grown area, parts turned inside out
+        // wind the opposite way.
+        resolve_regions(&curves, |p| Curve::winding_number(&raw, p) > 0)
+    }
+}
+
+/// Offset of a monotonic curve, quads are subdivided until the approximation is within `tolerance`.
+fn offset_monotone(curve: &Curve, distance: f32, tolerance: f32, depth: u32, out: &mut Vec<Curve>) {
+    let (q0, q2) = (
+        curve.start() + curve.normal(0.0) * distance,
+        curve.end() + curve.normal(1.0) * distance,
+    );
+    let (p0, p1, p2) = match *curve {
+        Curve::Line { .. } => {
+            out.push(Curve::Line { p0: q0, p1: q2 });
+            return;
+        }
+        Curve::Quad { p0, p1, p2 } => (p0, p1, p2),
+    };
+
+    // Control point at the intersection of the offset end tangents.
+    let (d0, d2) = (p1 - p0, p2 - p1);
+    let s = (q2 - q0).perp_dot(d2) / d0.perp_dot(d2);
+    let approx = Curve::Quad {
+        p0: q0,
+        p1: q0 + d0 * s,
+        p2: q2,
+    };
+
+    let error = [0.25, 0.5, 0.75]
+        .iter()
+        .map(|&t| approx.distance(curve.eval(t) + curve.normal(t) * distance))
+        .fold(0.0, f32::max);
+    if s.is_finite() && error <= tolerance {
+        out.push(approx);
+    } else if depth == 0 {
+        out.push(Curve::Line { p0: q0, p1: q2 });
+    } else {
+        let (c0, c1) = curve.split_at(0.5);
+        offset_monotone(&c0, distance, tolerance, depth - 1, out);
+        offset_monotone(&c1, distance, tolerance, depth - 1, out);
+    }
+}
+
+/// Circular arc around `center` from `from` to `to` along the shorter direction, approximated by quads.
+fn arc(center: glam::Vec2, from: glam::Vec2, to: glam::Vec2, tolerance: f32, out: &mut Vec<Curve>) {
+    let (v0, v1) = (from - center, to - center);
+    let radius = v0.length();
+    let start = v0.y().atan2(v0.x());
+    let sweep = v0.perp_dot(v1).atan2(v0.dot(v1));
+
+    let quads = arc_quads(center, glam::vec2(radius, radius), 0.0, start, sweep, tolerance);
+    let n = quads.len();
+    let mut p0 = from;
+    for (i, (p1, p2)) in quads.into_iter().enumerate() {
+        let p2 = if i + 1 == n { to } else { p2 };
+        out.push(Curve::Quad { p0, p1, p2 });
+        p0 = p2;
+    }
+}
+
+/// Offset curves of a closed contour connected by joins, may overlap itself.
+fn offset_contour(contour: &[Curve], distance: f32, join: Join, tolerance: f32) -> Segment {
+    let curves = Curve::monotize_path(contour);
+    let offsets = curves
+        .iter()
+        .map(|curve| {
+            let mut out = Vec::new();
+            offset_monotone(curve, distance, tolerance, OFFSET_MAX_DEPTH, &mut out);
+            out
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+    for (i, curve) in curves.iter().enumerate() {
+        let next = (i + 1) % curves.len();
+        result.extend_from_slice(&offsets[i]);
+
+        let p = curve.end();
+        let (a, b) = (offsets[i][offsets[i].len() - 1].end(), offsets[next][0].start());
+        if a == b {
+            continue;
+        }
+
+        let (n0, n1) = (curve.normal(1.0), curves[next].normal(0.0));
+        if distance * n0.perp_dot(n1) >= 0.0 {
+            // Inner corner, connect through the corner point. The resulting loop winds
+            // in the same direction as the outline and is removed later.
+            result.push(Curve::Line { p0: a, p1: p });
+            result.push(Curve::Line { p0: p, p1: b });
+            continue;
+        }
+
+        match join {
+            Join::Round => arc(p, a, b, tolerance, &mut result),
+            Join::Miter { limit } => {
+                let cos = n0.dot(n1);
+                let tip = p + (n0 + n1) * (distance / (1.0 + cos));
+                if (tip - p).length() <= limit * distance.abs() {
+                    result.push(Curve::Line { p0: a, p1: tip });
+                    result.push(Curve::Line { p0: tip, p1: b });
+                } else {
+                    result.push(Curve::Line { p0: a, p1: b });
+                }
+            }
+            Join::Bevel => result.push(Curve::Line { p0: a, p1: b }),
+        }
+    }
+    result
+}
+
+impl Path {
+    /// See `Curve::offset_segments`.
+    pub fn offset(&self, distance: f32, join: Join, tolerance: f32) -> Path {
+        Path::from_segments(&Curve::offset_segments(&self.to_segments(), distance, join, tolerance))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hit_test, Aabb, FillRule, PathBuilder};
+
+    fn square(x: f32, y: f32, size: f32) -> Segment {
+        PathBuilder::new()
+            .move_to(glam::vec2(x, y))
+            .line_to(glam::vec2(x, y + size))
+            .line_to(glam::vec2(x + size, y + size))
+            .line_to(glam::vec2(x + size, y))
+            .close()
+            .finish()
+    }
+
+    fn area(segments: &[Segment]) -> f32 {
+        -segments.iter().map(|segment| Curve::contour_area(segment)).sum::<f32>()
+    }
+
+    /// Check a round offset against the distance to the input on a grid of points.
+    fn check(segments: &[Segment], distance: f32) -> Vec<Segment> {
+        let result = Curve::offset_segments(segments, distance, Join::Round, 0.01);
+        assert_eq!(Curve::validate_segments(&result), Ok(()));
+
+        let bounds = Aabb::from_segments(segments).expand(glam::vec2(distance.abs() + 1.0, distance.abs() + 1.0));
+        let n = 41;
+        for i in 0..=n {
+            for j in 0..=n {
+                let p = bounds.min + bounds.extent() * glam::vec2(i as f32 + 0.31, j as f32 + 0.17) / (n + 1) as f32;
+                let d = segments.iter().flatten().map(|curve| curve.distance(p)).fold(f32::MAX, f32::min);
+                if (d - distance.abs()).abs() < 0.05 {
+                    continue;
+                }
+
+                let inside = hit_test(segments, p, FillRule::NonZero);
+                let expected = if distance > 0.0 {
+                    inside || d < distance
+                } else {
+                    inside && d > -distance
+                };
+                assert_eq!(Curve::winding_number(&result, p), expected as i32, "{} at {:?}", distance, p);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn joins() {
+        let a = vec![square(0.0, 0.0, 10.0)];
+
+        let miter = Curve::offset_segments(&a, 1.0, Join::Miter { limit: 4.0 }, 0.01);
+        assert_eq!(miter.len(), 1);
+        assert_eq!(miter[0].len(), 4);
+        assert!((area(&miter) - 144.0).abs() < 1e-3);
+        assert!((area(&Curve::offset_segments(&a, 1.0, Join::Bevel, 0.01)) - 142.0).abs() < 1e-3);
+        // Right angles exceed a miter limit below `sqrt(2)`.
+        assert!((area(&Curve::offset_segments(&a, 1.0, Join::Miter { limit: 1.2 }, 0.01)) - 142.0).abs() < 1e-3);
+        let round = check(&a, 1.0);
+        assert!((area(&round) - (140.0 + std::f32::consts::PI)).abs() < 0.05);
+
+        for &join in &[Join::Round, Join::Miter { limit: 4.0 }, Join::Bevel] {
+            assert!((area(&Curve::offset_segments(&a, -1.0, join, 0.01)) - 64.0).abs() < 1e-3);
+            assert!(Curve::offset_segments(&a, -6.0, join, 0.01).is_empty());
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        // Concave corner.
+        let l = vec![PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(0.0, 10.0))
+            .line_to(glam::vec2(10.0, 10.0))
+            .line_to(glam::vec2(10.0, 6.0))
+            .line_to(glam::vec2(4.0, 6.0))
+            .line_to(glam::vec2(4.0, 0.0))
+            .close()
+            .finish()];
+        check(&l, 1.5);
+        check(&l, -1.0);
+        // Thin stem vanishes.
+        assert_eq!(check(&l, -2.5).len(), 0);
+
+        // Hole closes when growing.
+        let holed = vec![square(0.0, 0.0, 10.0), Curve::reverse_contour(&square(3.0, 3.0, 4.0))];
+        assert_eq!(check(&holed, 1.0).len(), 2);
+        assert_eq!(check(&holed, 2.5).len(), 1);
+        check(&holed, -1.0);
+
+        let circle = vec![PathBuilder::new()
+            .move_to(glam::vec2(18.0, 10.0))
+            .quad_to(glam::vec2(18.0, 18.0), glam::vec2(10.0, 18.0))
+            .quad_to(glam::vec2(2.0, 18.0), glam::vec2(2.0, 10.0))
+            .quad_to(glam::vec2(2.0, 2.0), glam::vec2(10.0, 2.0))
+            .quad_to(glam::vec2(18.0, 2.0), glam::vec2(18.0, 10.0))
+            .finish()];
+        let circle = Curve::fix_orientation(&circle);
+        check(&circle, 2.0);
+        check(&circle, -3.0);
+        check(&circle, -7.0);
+        let grown = Path::from_segments(&circle).offset(2.0, Join::Round, 0.01);
+        assert_eq!(grown.contours().count(), 1);
+    }
+
+    #[test]
+    fn curve_offset() {
+        let arch = Curve::Quad {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(5.0, 10.0),
+            p2: glam::vec2(10.0, 0.0),
+        };
+        for &distance in &[-2.0, -0.5, 0.5, 3.0] {
+            let offset = arch.offset(distance, 0.01);
+            assert!((offset[0].start() - (arch.start() + arch.normal(0.0) * distance)).length() < 1e-4);
+            assert!((offset[offset.len() - 1].end() - (arch.end() + arch.normal(1.0) * distance)).length() < 1e-4);
+            for curve in &offset {
+                for i in 0..=8 {
+                    let d = arch.distance(curve.eval(i as f32 / 8.0));
+                    assert!((d - f32::abs(distance)).abs() < 0.02, "{} {}", distance, d);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Orientation and winding of closed contours.
+
+use crate::{math::*, Contour, Curve, Path, Segment};
+
+/// Direction of a contour as seen on screen (y pointing down).
+///
+/// The rasterizers fill counter-clockwise contours, clockwise contours cut holes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Orientation {
+    pub fn reversed(self) -> Orientation {
+        match self {
+            Orientation::Clockwise => Orientation::CounterClockwise,
+            Orientation::CounterClockwise => Orientation::Clockwise,
+        }
+    }
+}
+
+impl Curve {
+    /// Exact signed area of a closed contour, negative for counter-clockwise contours.
+    pub fn contour_area(contour: &[Curve]) -> f32 {
+        contour.iter().map(Curve::signed_area).sum()
+    }
+
+    /// `None` for contours without enclosed area.
+    pub fn contour_orientation(contour: &[Curve]) -> Option<Orientation> {
+        let area = Curve::contour_area(contour);
+        if area < 0.0 {
+            Some(Orientation::CounterClockwise)
+        } else if area > 0.0 {
+            Some(Orientation::Clockwise)
+        } else {
+            None
+        }
+    }
+
+    /// Same contour traversed in opposite direction.
+    pub fn reverse_contour(contour: &[Curve]) -> Segment {
+        contour.iter().rev().map(Curve::reversed).collect()
+    }
+
+    /// Signed number of crossings of the ray from `p` towards +x with a curve.
+    ///
+    /// Counter-clockwise contours contribute +1 to points inside. Like the rasterizers, a
+    /// crossing is counted if the curve ends on different sides of the ray, so touching
+    /// points are ignored.
+    fn crossings(&self, p: glam::Vec2) -> i32 {
+        self.monotonize()
+            .iter()
+            .map(|curve| {
+                let (p0, p1) = (curve.start() - p, curve.end() - p);
+                let sign_y = (p0.y() > 0.0) as i32 - (p1.y() > 0.0) as i32;
+                if sign_y == 0 {
+                    return 0;
+                }
+
+                let x = match *curve {
+                    Curve::Line { .. } => line_eval(p0.x(), p1.x(), line_raycast(p0.y(), p1.y(), 0.0)),
+                    Curve::Quad { p1: c, .. } => {
+                        let c = c - p;
+                        let t = quad_raycast(p0.y(), c.y(), p1.y(), 0.0);
+                        quad_eval(p0.x(), c.x(), p1.x(), t)
+                    }
+                };
+                if x > 0.0 {
+                    sign_y
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    /// Winding number of `p` with respect to closed contours.
+    ///
+    /// Positive inside counter-clockwise contours, the point is filled if non-zero (`FillRule::NonZero`)
+    /// or odd (`FillRule::EvenOdd`).
+    pub fn winding_number(contours: &[Segment], p: glam::Vec2) -> i32 {
+        contours.iter().flatten().map(|curve| curve.crossings(p)).sum()
+    }
+
+    /// Orient contours by nesting depth: outer contours counter-clockwise, holes clockwise,
+    /// islands inside holes counter-clockwise again and so on.
+    ///
+    /// Useful for outlines with inconsistent directions, overlapping contours are assumed to be disjoint or nested.
+    pub fn fix_orientation(contours: &[Segment]) -> Vec<Segment> {
+        contours
+            .iter()
+            .enumerate()
+            .map(|(i, contour)| {
+                let orientation = match Curve::contour_orientation(contour) {
+                    Some(orientation) => orientation,
+                    None => return contour.clone(),
+                };
+
+                let p = contour[0].eval(0.5);
+                let depth = contours
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, other)| {
+                        j != i && contour_contains(other, p) && Curve::contour_area(other).abs() > Curve::contour_area(contour).abs()
+                    })
+                    .count();
+                let expected = if depth % 2 == 0 {
+                    Orientation::CounterClockwise
+                } else {
+                    Orientation::Clockwise
+                };
+
+                if orientation == expected {
+                    contour.clone()
+                } else {
+                    Curve::reverse_contour(contour)
+                }
+            })
+            .collect()
+    }
+}
+
+fn contour_contains(contour: &[Curve], p: glam::Vec2) -> bool {
+    contour.iter().map(|curve| curve.crossings(p)).sum::<i32>() != 0
+}
+
+impl<'a> Contour<'a> {
+    /// Signed area including the closing line, see `Curve::contour_area`.
+    pub fn signed_area(&self) -> f32 {
+        self.curves().map(|curve| curve.signed_area()).sum()
+    }
+
+    pub fn orientation(&self) -> Option<Orientation> {
+        Curve::contour_orientation(&self.to_segment())
+    }
+}
+
+impl Path {
+    /// All subpaths traversed in opposite direction, open subpaths stay open.
+    pub fn reversed(&self) -> Path {
+        self.map_contours(Curve::reverse_contour)
+    }
+
+    /// Winding number of `p`, open subpaths are treated as closed.
+    pub fn winding_number(&self, p: glam::Vec2) -> i32 {
+        let mut contours = self.to_segments();
+        for contour in &mut contours {
+            let (start, end) = (contour[0].start(), contour[contour.len() - 1].end());
+            if start != end {
+                contour.push(Curve::Line { p0: end, p1: start });
+            }
+        }
+        Curve::winding_number(&contours, p)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PathBuilder;
+
+    fn square(offset: f32, size: f32) -> Segment {
+        PathBuilder::new()
+            .move_to(glam::vec2(offset, offset))
+            .line_to(glam::vec2(offset, offset + size))
+            .line_to(glam::vec2(offset + size, offset + size))
+            .line_to(glam::vec2(offset + size, offset))
+            .close()
+            .finish()
+    }
+
+    #[test]
+    fn orientation() {
+        let outer = square(0.0, 10.0);
+        assert_eq!(Curve::contour_area(&outer), -100.0);
+        assert_eq!(Curve::contour_orientation(&outer), Some(Orientation::CounterClockwise));
+
+        let reversed = Curve::reverse_contour(&outer);
+        assert_eq!(Curve::contour_orientation(&reversed), Some(Orientation::Clockwise));
+        assert_eq!(reversed[0].start(), outer[outer.len() - 1].end());
+
+        // Exact for quads: a parabolic segment covers 2/3 of its bounding box.
+        let arch = PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .quad_to(glam::vec2(5.0, 10.0), glam::vec2(10.0, 0.0))
+            .close()
+            .finish();
+        assert!((Curve::contour_area(&arch).abs() - 2.0 / 3.0 * 10.0 * 5.0).abs() < 1e-4);
+
+        let path = PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(0.0, 10.0))
+            .line_to(glam::vec2(10.0, 10.0))
+            .close()
+            .finish_path();
+        let contour = path.contours().next().unwrap();
+        assert_eq!(contour.signed_area(), -50.0);
+        assert_eq!(path.reversed().contours().next().unwrap().orientation(), Some(Orientation::Clockwise));
+    }
+
+    #[test]
+    fn winding() {
+        let contours = vec![square(0.0, 10.0), square(2.0, 2.0), Curve::reverse_contour(&square(6.0, 2.0))];
+
+        assert_eq!(Curve::winding_number(&contours, glam::vec2(1.0, 1.0)), 1);
+        assert_eq!(Curve::winding_number(&contours, glam::vec2(3.0, 3.0)), 2);
+        assert_eq!(Curve::winding_number(&contours, glam::vec2(7.0, 7.0)), 0);
+        assert_eq!(Curve::winding_number(&contours, glam::vec2(-1.0, 5.0)), 0);
+        // Ray through a vertex.
+        assert_eq!(Curve::winding_number(&contours, glam::vec2(1.0, 6.0)), 1);
+
+        let circle = PathBuilder::new()
+            .move_to(glam::vec2(10.0, 0.0))
+            .quad_to(glam::vec2(0.0, 0.0), glam::vec2(0.0, 10.0))
+            .quad_to(glam::vec2(0.0, 20.0), glam::vec2(10.0, 20.0))
+            .quad_to(glam::vec2(20.0, 20.0), glam::vec2(20.0, 10.0))
+            .quad_to(glam::vec2(20.0, 0.0), glam::vec2(10.0, 0.0))
+            .finish_path();
+        assert_eq!(circle.winding_number(glam::vec2(10.0, 10.0)), 1);
+        assert_eq!(circle.winding_number(glam::vec2(1.0, 1.0)), 0);
+        assert_eq!(circle.reversed().winding_number(glam::vec2(10.0, 10.0)), -1);
+    }
+
+    #[test]
+    fn fix_orientation() {
+        // All contours clockwise: outer, hole and island.
+        let contours = vec![
+            Curve::reverse_contour(&square(0.0, 10.0)),
+            Curve::reverse_contour(&square(2.0, 6.0)),
+            Curve::reverse_contour(&square(4.0, 2.0)),
+        ];
+        let fixed = Curve::fix_orientation(&contours);
+        let orientations = fixed.iter().map(|contour| Curve::contour_orientation(contour).unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            orientations,
+            [Orientation::CounterClockwise, Orientation::Clockwise, Orientation::CounterClockwise]
+        );
+        assert_eq!(Curve::winding_number(&fixed, glam::vec2(3.0, 3.0)), 0);
+        assert_eq!(Curve::winding_number(&fixed, glam::vec2(5.0, 5.0)), 1);
+    }
+}
@@ -0,0 +1,317 @@
+//! Paths stored as drawing commands, keeping subpath boundaries.
+
+use crate::{Aabb, Curve, NormalizeOptions, PathError, Segment};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verb {
+    /// Start a new subpath, one point.
+    MoveTo,
+    /// One point.
+    LineTo,
+    /// Control and end point.
+    QuadTo,
+    /// Connect back to the start of the subpath, no points.
+    Close,
+}
+
+impl Verb {
+    pub fn num_points(self) -> usize {
+        match self {
+            Verb::MoveTo | Verb::LineTo => 1,
+            Verb::QuadTo => 2,
+            Verb::Close => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Subpath {
+    verbs: Range<usize>,
+    points: Range<usize>,
+}
+
+/// Sequence of subpaths, each starting with `MoveTo` and optionally ending with `Close`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    verbs: Vec<Verb>,
+    points: Vec<glam::Vec2>,
+    subpaths: Vec<Subpath>,
+}
+
+/// Borrowed subpath of a `Path`.
+#[derive(Debug, Clone, Copy)]
+pub struct Contour<'a> {
+    pub verbs: &'a [Verb],
+    pub points: &'a [glam::Vec2],
+}
+
+impl<'a> Contour<'a> {
+    pub fn start(&self) -> glam::Vec2 {
+        self.points[0]
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.verbs.last() == Some(&Verb::Close)
+    }
+
+    /// Curves of the contour, including the implicit closing line if it has non-zero length.
+    pub fn curves(&self) -> impl Iterator<Item = Curve> + 'a {
+        let start = self.start();
+        let points = self.points;
+        let mut current = start;
+        let mut idx = 1;
+        self.verbs[1..].iter().filter_map(move |verb| {
+            let curve = match verb {
+                Verb::LineTo => Curve::Line {
+                    p0: current,
+                    p1: points[idx],
+                },
+                Verb::QuadTo => Curve::Quad {
+                    p0: current,
+                    p1: points[idx],
+                    p2: points[idx + 1],
+                },
+                Verb::Close if current != start => Curve::Line { p0: current, p1: start },
+                Verb::MoveTo | Verb::Close => return None,
+            };
+            idx += verb.num_points();
+            current = curve.end();
+            Some(curve)
+        })
+    }
+
+    pub fn to_segment(&self) -> Segment {
+        self.curves().collect()
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// Subpaths as individual segments, closed contours end at their start.
+    pub fn from_segments(segments: &[Segment]) -> Self {
+        let mut path = Path::new();
+        for segment in segments {
+            path.push_contour(segment, false);
+        }
+        path
+    }
+
+    /// Split a flat list of curves into subpaths wherever consecutive curves are not connected.
+    pub fn from_curves(curves: &[Curve]) -> Self {
+        let mut segments: Vec<Segment> = Vec::new();
+        for curve in curves {
+            match segments.last_mut() {
+                Some(segment) if segment[segment.len() - 1].end() == curve.start() => segment.push(*curve),
+                _ => segments.push(vec![*curve]),
+            }
+        }
+        Path::from_segments(&segments)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verbs.is_empty()
+    }
+
+    pub fn verbs(&self) -> &[Verb] {
+        &self.verbs
+    }
+
+    pub fn points(&self) -> &[glam::Vec2] {
+        &self.points
+    }
+
+    /// Current end point, the start of the last subpath if it has been closed.
+    pub fn current(&self) -> Option<glam::Vec2> {
+        let subpath = self.subpaths.last()?;
+        if self.verbs[subpath.verbs.end - 1] == Verb::Close {
+            Some(self.points[subpath.points.start])
+        } else {
+            self.points.last().cloned()
+        }
+    }
+
+    pub fn move_to(&mut self, p: glam::Vec2) {
+        // Drop subpaths without any drawing command.
+        if let Some(subpath) = self.subpaths.last() {
+            if subpath.verbs.len() == 1 {
+                self.verbs.pop();
+                self.points.pop();
+                self.subpaths.pop();
+            }
+        }
+
+        self.subpaths.push(Subpath {
+            verbs: self.verbs.len()..self.verbs.len() + 1,
+            points: self.points.len()..self.points.len() + 1,
+        });
+        self.verbs.push(Verb::MoveTo);
+        self.points.push(p);
+    }
+
+    pub fn line_to(&mut self, p: glam::Vec2) {
+        self.push(Verb::LineTo, &[p]);
+    }
+
+    pub fn quad_to(&mut self, p1: glam::Vec2, p2: glam::Vec2) {
+        self.push(Verb::QuadTo, &[p1, p2]);
+    }
+
+    /// Close the current subpath, drawing continues from its start.
+    pub fn close(&mut self) {
+        if !self.subpaths.is_empty() && !self.verbs.ends_with(&[Verb::Close]) {
+            self.push(Verb::Close, &[]);
+        }
+    }
+
+    /// Append curves as new subpath, closed if requested or if it ends at its start.
+    fn push_contour(&mut self, curves: &[Curve], close: bool) {
+        let (first, last) = match (curves.first(), curves.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+        let close = close || first.start() == last.end();
+        // The closing line is implied by `Close`.
+        let curves = match last {
+            Curve::Line { p1, .. } if close && *p1 == first.start() => &curves[..curves.len() - 1],
+            _ => curves,
+        };
+
+        self.move_to(first.start());
+        for curve in curves {
+            match *curve {
+                Curve::Line { p1, .. } => self.line_to(p1),
+                Curve::Quad { p1, p2, .. } => self.quad_to(p1, p2),
+            }
+        }
+        if close {
+            self.close();
+        }
+    }
+
+    fn push(&mut self, verb: Verb, points: &[glam::Vec2]) {
+        // Drawing after `close` or without `move_to` implicitly starts a new subpath.
+        match self.subpaths.last() {
+            None => self.move_to(glam::vec2(0.0, 0.0)),
+            Some(subpath) if self.verbs[subpath.verbs.end - 1] == Verb::Close => {
+                let start = self.points[subpath.points.start];
+                self.move_to(start);
+            }
+            Some(_) => {}
+        }
+
+        self.verbs.push(verb);
+        self.points.extend_from_slice(points);
+        let subpath = self.subpaths.last_mut().unwrap();
+        subpath.verbs.end = self.verbs.len();
+        subpath.points.end = self.points.len();
+    }
+
+    pub fn contours(&self) -> impl Iterator<Item = Contour<'_>> + '_ {
+        self.subpaths.iter().map(move |subpath| Contour {
+            verbs: &self.verbs[subpath.verbs.clone()],
+            points: &self.points[subpath.points.clone()],
+        })
+    }
+
+    /// All curves of the path, see `Contour::curves`.
+    pub fn curves(&self) -> impl Iterator<Item = Curve> + '_ {
+        self.contours().flat_map(|contour| contour.curves())
+    }
+
+    /// One segment per subpath.
+    pub fn to_segments(&self) -> Vec<Segment> {
+        self.contours()
+            .map(|contour| contour.to_segment())
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        self.curves().fold(Aabb::empty(), |aabb, curve| aabb.union(&curve.aabb()))
+    }
+
+    /// Apply `f` to the curves of each contour.
+    ///
+    /// Contours ending at their start point afterwards are closed.
+    pub fn map_contours<F: FnMut(&[Curve]) -> Vec<Curve>>(&self, mut f: F) -> Path {
+        let mut path = Path::new();
+        for contour in self.contours() {
+            path.push_contour(&f(&contour.to_segment()), contour.is_closed());
+        }
+        path
+    }
+
+    /// Report unclosed and disconnected contours, non-finite coordinates and
+    /// zero length curves. Points are compared exactly.
+    pub fn validate(&self) -> Result<(), Vec<PathError>> {
+        Curve::validate_segments(&self.to_segments())
+    }
+
+    /// Clean up contours for rasterization.
+    ///
+    /// Contours with non-finite coordinates are dropped entirely, zero length curves are
+    /// removed and small gaps are snapped shut. Larger gaps are bridged with lines.
+    pub fn normalize(&self, options: &NormalizeOptions) -> Path {
+        Path::from_segments(&Curve::normalize_segments(&self.to_segments(), options))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn subpaths() {
+        let mut path = Path::new();
+        path.move_to(glam::vec2(0.0, 0.0));
+        path.line_to(glam::vec2(10.0, 0.0));
+        path.quad_to(glam::vec2(10.0, 10.0), glam::vec2(0.0, 10.0));
+        path.close();
+        // Continues from the start of the closed subpath.
+        path.line_to(glam::vec2(-10.0, 0.0));
+        path.move_to(glam::vec2(100.0, 100.0));
+        path.move_to(glam::vec2(50.0, 50.0));
+        path.line_to(glam::vec2(60.0, 50.0));
+
+        let contours = path.contours().collect::<Vec<_>>();
+        assert_eq!(contours.len(), 3);
+        assert!(contours[0].is_closed());
+        assert!(!contours[1].is_closed());
+        assert_eq!(contours[1].start(), glam::vec2(0.0, 0.0));
+        assert_eq!(contours[2].start(), glam::vec2(50.0, 50.0));
+        assert_eq!(path.verbs()[..5], [Verb::MoveTo, Verb::LineTo, Verb::QuadTo, Verb::Close, Verb::MoveTo]);
+
+        let segments = path.to_segments();
+        assert_eq!(segments.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1, 1]);
+        assert_eq!(segments[0][2].end(), glam::vec2(0.0, 0.0));
+        assert_eq!(path.current(), Some(glam::vec2(60.0, 50.0)));
+    }
+
+    #[test]
+    fn conversions() {
+        let mut path = Path::new();
+        path.move_to(glam::vec2(0.0, 0.0));
+        path.line_to(glam::vec2(10.0, 0.0));
+        path.line_to(glam::vec2(10.0, 10.0));
+        path.close();
+        path.move_to(glam::vec2(20.0, 0.0));
+        path.quad_to(glam::vec2(30.0, 10.0), glam::vec2(20.0, 20.0));
+
+        let segments = path.to_segments();
+        assert_eq!(Path::from_segments(&segments), path);
+
+        let curves = path.curves().collect::<Vec<_>>();
+        assert_eq!(curves.len(), 4);
+        assert_eq!(Path::from_curves(&curves), path);
+
+        assert_eq!(path.validate(), Err(vec![PathError::Unclosed { contour: 1 }]));
+        assert_eq!(path.normalize(&NormalizeOptions::default()).validate(), Ok(()));
+
+        let aabb = path.aabb();
+        assert_eq!((aabb.min, aabb.max), (glam::vec2(0.0, 0.0), glam::vec2(25.0, 20.0)));
+    }
+}
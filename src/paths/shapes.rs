@@ -0,0 +1,182 @@
+//! Closed shape primitives for `PathBuilder`.
+
+use super::arc::arc_quads;
+use crate::{Extent, Offset, PathBuilder};
+use std::f32::consts::PI;
+
+impl PathBuilder {
+    /// Closed axis aligned rectangle.
+    ///
+    /// Like all shapes it forms a new subpath, oriented counter-clockwise to be filled by the rasterizers.
+    pub fn rect(self, offset: Offset, extent: Extent) -> Self {
+        self.move_to(offset)
+            .line_to(offset + glam::vec2(0.0, extent.y()))
+            .line_to(offset + extent)
+            .line_to(offset + glam::vec2(extent.x(), 0.0))
+            .close()
+    }
+
+    /// Rectangle with circular corners, radii given as `[top_left, top_right, bottom_right, bottom_left]`.
+    ///
+    /// Radii are scaled down uniformly if adjacent corners would overlap (same as CSS `border-radius`).
+    pub fn rounded_rect(mut self, offset: Offset, extent: Extent, radii: [f32; 4]) -> Self {
+        let (w, h) = (extent.x(), extent.y());
+        let [tl, tr, br, bl] = radii;
+        let (tl, tr, br, bl) = (tl.max(0.0), tr.max(0.0), br.max(0.0), bl.max(0.0));
+        let fit = |side: f32, r: f32| if r > side { side / r } else { 1.0 };
+        let scale = fit(w, tl + tr).min(fit(w, bl + br)).min(fit(h, tl + bl)).min(fit(h, tr + br));
+        let (tl, tr, br, bl) = (tl * scale, tr * scale, br * scale, bl * scale);
+
+        // Corners as center, radius, start angle and end points, each turning a quarter counter-clockwise.
+        let (x0, y0, x1, y1) = (offset.x(), offset.y(), offset.x() + w, offset.y() + h);
+        let corners = [
+            (glam::vec2(x0 + bl, y1 - bl), bl, PI, glam::vec2(x0, y1 - bl), glam::vec2(x0 + bl, y1)),
+            (glam::vec2(x1 - br, y1 - br), br, 0.5 * PI, glam::vec2(x1 - br, y1), glam::vec2(x1, y1 - br)),
+            (glam::vec2(x1 - tr, y0 + tr), tr, 0.0, glam::vec2(x1, y0 + tr), glam::vec2(x1 - tr, y0)),
+            (glam::vec2(x0 + tl, y0 + tl), tl, -0.5 * PI, glam::vec2(x0 + tl, y0), glam::vec2(x0, y0 + tl)),
+        ];
+
+        self = self.move_to(glam::vec2(x0, y0 + tl));
+        for &(center, radius, start, from, to) in &corners {
+            if self.path.current() != Some(from) {
+                self = self.line_to(from);
+            }
+            if radius > 0.0 {
+                let quads = arc_quads(center, glam::vec2(radius, radius), 0.0, start, -0.5 * PI, self.tolerance);
+                let n = quads.len();
+                for (i, (ctrl, p2)) in quads.into_iter().enumerate() {
+                    self = self.quad_to(ctrl, if i + 1 == n { to } else { p2 });
+                }
+            }
+        }
+        self.close()
+    }
+
+    pub fn circle(self, center: glam::Vec2, radius: f32) -> Self {
+        self.ellipse(center, glam::vec2(radius, radius))
+    }
+
+    /// Axis aligned ellipse.
+    pub fn ellipse(mut self, center: glam::Vec2, radii: glam::Vec2) -> Self {
+        let start = center + glam::vec2(radii.x(), 0.0);
+        self = self.move_to(start);
+        let quads = arc_quads(center, radii, 0.0, 0.0, -2.0 * PI, self.tolerance);
+        let n = quads.len();
+        for (i, (ctrl, p2)) in quads.into_iter().enumerate() {
+            self = self.quad_to(ctrl, if i + 1 == n { start } else { p2 });
+        }
+        self.close()
+    }
+
+    /// Regular polygon with a vertex pointing up (towards -y), ignored for less than 3 sides.
+    pub fn regular_polygon(self, center: glam::Vec2, radius: f32, sides: u32) -> Self {
+        if sides < 3 {
+            return self;
+        }
+        self.star_polygon(center, &[radius], sides)
+    }
+
+    /// Star with `points` tips alternating with inner vertices, ignored for less than 2 points.
+    pub fn star(self, center: glam::Vec2, outer_radius: f32, inner_radius: f32, points: u32) -> Self {
+        if points < 2 {
+            return self;
+        }
+        self.star_polygon(center, &[outer_radius, inner_radius], points)
+    }
+
+    /// Polygon with `n` repetitions of vertices at the given radii, evenly spaced in angle.
+    fn star_polygon(mut self, center: glam::Vec2, radii: &[f32], n: u32) -> Self {
+        let count = n as usize * radii.len();
+        let step = 2.0 * PI / count as f32;
+        for i in 0..count {
+            let (sin, cos) = (-0.5 * PI - step * i as f32).sin_cos();
+            let p = center + glam::vec2(cos, sin) * radii[i % radii.len()];
+            self = if i == 0 { self.move_to(p) } else { self.line_to(p) };
+        }
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Curve, Segment};
+
+    fn area(segments: &[Segment]) -> f32 {
+        -segments.iter().map(|segment| Curve::contour_area(segment)).sum::<f32>()
+    }
+
+    fn assert_closed(path: &PathBuilder) {
+        let path = path.path.clone();
+        assert_eq!(path.validate(), Ok(()));
+        assert!(path.contours().all(|contour| contour.is_closed()));
+    }
+
+    #[test]
+    fn ellipses() {
+        let tolerance = 0.01;
+        let circle = PathBuilder::new()
+            .with_tolerance(tolerance)
+            .circle(glam::vec2(10.0, 20.0), 5.0);
+        assert_closed(&circle);
+        let curves = circle.finish();
+        for curve in &curves {
+            for i in 0..=8 {
+                let r = (curve.eval(i as f32 / 8.0) - glam::vec2(10.0, 20.0)).length();
+                assert!((r - 5.0).abs() <= tolerance);
+            }
+        }
+        // Quads lie outside of the circle, within the tolerance.
+        assert!((area(&[curves]) - 25.0 * PI).abs() < 10.0 * PI * tolerance);
+
+        let ellipse = PathBuilder::new().with_tolerance(tolerance).ellipse(glam::vec2(0.0, 0.0), glam::vec2(8.0, 2.0));
+        let curves = ellipse.finish();
+        assert!((area(std::slice::from_ref(&curves)) - 16.0 * PI).abs() < 40.0 * tolerance);
+        let aabb = crate::Aabb::from_curves(&curves);
+        assert!((aabb.max - glam::vec2(8.0, 2.0)).length() < 1e-3);
+
+        // Coarser tolerance needs fewer quads.
+        let coarse = PathBuilder::new().with_tolerance(1.0).circle(glam::vec2(0.0, 0.0), 5.0).finish();
+        let fine = PathBuilder::new().with_tolerance(0.001).circle(glam::vec2(0.0, 0.0), 5.0).finish();
+        assert!(coarse.len() < fine.len());
+    }
+
+    #[test]
+    fn rects() {
+        let rect = PathBuilder::new().rect(glam::vec2(1.0, 2.0), glam::vec2(3.0, 4.0));
+        assert_closed(&rect);
+        assert_eq!(area(&[rect.finish()]), 12.0);
+
+        let rounded = PathBuilder::new()
+            .with_tolerance(0.001)
+            .rounded_rect(glam::vec2(0.0, 0.0), glam::vec2(20.0, 10.0), [0.0, 2.0, 4.0, 1.0]);
+        assert_closed(&rounded);
+        let expected = 200.0 - (4.0 + 16.0 + 1.0) * (1.0 - 0.25 * PI);
+        assert!((area(&[rounded.finish()]) - expected).abs() < 0.01);
+
+        // Radii scaled down to fit, resulting in a stadium.
+        let stadium = PathBuilder::new()
+            .with_tolerance(0.001)
+            .rounded_rect(glam::vec2(0.0, 0.0), glam::vec2(20.0, 10.0), [10.0; 4])
+            .finish();
+        assert!((area(&[stadium]) - (100.0 + 25.0 * PI)).abs() < 0.05);
+    }
+
+    #[test]
+    fn polygons() {
+        let hexagon = PathBuilder::new().regular_polygon(glam::vec2(0.0, 0.0), 2.0, 6);
+        assert_closed(&hexagon);
+        let hexagon = hexagon.finish();
+        assert_eq!(hexagon.len(), 6);
+        assert!((hexagon[0].start() - glam::vec2(0.0, -2.0)).length() < 1e-6);
+        assert!((area(&[hexagon]) - 1.5 * 3.0f32.sqrt() * 4.0).abs() < 1e-4);
+
+        let star = PathBuilder::new().star(glam::vec2(0.0, 0.0), 4.0, 1.0, 5).finish();
+        assert_eq!(star.len(), 10);
+        // Ten triangles between the center and consecutive vertices.
+        let expected = 10.0 * 0.5 * 4.0 * 1.0 * (0.2 * PI).sin();
+        assert!((area(&[star]) - expected).abs() < 1e-4);
+
+        assert!(PathBuilder::new().regular_polygon(glam::vec2(0.0, 0.0), 1.0, 2).finish().is_empty());
+    }
+}
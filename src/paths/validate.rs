@@ -0,0 +1,274 @@
+//! Checks and cleanup of contours before handing them to the rasterizers.
+
+use crate::{Curve, Segment};
+use std::fmt;
+
+/// Defect of a contour found by `Path::validate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathError {
+    /// Last curve doesn't end at the start of the contour, the winding is unbalanced.
+    Unclosed { contour: usize },
+    /// Curve doesn't start at the end of the previous one.
+    Disconnected { contour: usize, curve: usize },
+    NonFinite { contour: usize, curve: usize },
+    ZeroLength { contour: usize, curve: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Unclosed { contour } => write!(f, "contour {} is not closed", contour),
+            PathError::Disconnected { contour, curve } => {
+                write!(f, "curve {} of contour {} is not connected to its predecessor", curve, contour)
+            }
+            PathError::NonFinite { contour, curve } => {
+                write!(f, "curve {} of contour {} has non-finite coordinates", curve, contour)
+            }
+            PathError::ZeroLength { contour, curve } => write!(f, "curve {} of contour {} has zero length", curve, contour),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Cleanup steps of `Path::normalize`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizeOptions {
+    /// Connect the end of open contours to their start with a line.
+    pub close: bool,
+    /// Replace consecutive lines along the same direction by a single line.
+    pub merge_collinear: bool,
+    /// Distance (in curve space) below which points are considered equal.
+    pub tolerance: f32,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        NormalizeOptions {
+            close: true,
+            merge_collinear: true,
+            tolerance: 1e-4,
+        }
+    }
+}
+
+fn is_finite(curve: &Curve) -> bool {
+    let finite = |p: glam::Vec2| p.x().is_finite() && p.y().is_finite();
+    match *curve {
+        Curve::Line { p0, p1 } => finite(p0) && finite(p1),
+        Curve::Quad { p0, p1, p2 } => finite(p0) && finite(p1) && finite(p2),
+    }
+}
+
+/// All control points lie within `tolerance` of the start.
+fn is_degenerate(curve: &Curve, tolerance: f32) -> bool {
+    let near = |p: glam::Vec2| (p - curve.start()).length() <= tolerance;
+    match *curve {
+        Curve::Line { p1, .. } => near(p1),
+        Curve::Quad { p1, p2, .. } => near(p1) && near(p2),
+    }
+}
+
+/// Line spanning both lines if they continue in the same direction.
+fn merge_lines(a: &Curve, b: &Curve, tolerance: f32) -> Option<Curve> {
+    match (*a, *b) {
+        (Curve::Line { p0, p1: mid }, Curve::Line { p1, .. }) => {
+            let dir = p1 - p0;
+            let length = dir.length();
+            let forward = (mid - p0).dot(dir) > 0.0 && (p1 - mid).dot(dir) > 0.0;
+            if forward && dir.perp_dot(mid - p0).abs() <= tolerance * length {
+                Some(Curve::Line { p0, p1 })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Move the start of the curve to `p`.
+fn with_start(curve: &Curve, p: glam::Vec2) -> Curve {
+    match *curve {
+        Curve::Line { p1, .. } => Curve::Line { p0: p, p1 },
+        Curve::Quad { p1, p2, .. } => Curve::Quad { p0: p, p1, p2 },
+    }
+}
+
+/// Move the end of the curve to `p`.
+fn with_end(curve: &Curve, p: glam::Vec2) -> Curve {
+    match *curve {
+        Curve::Line { p0, .. } => Curve::Line { p0, p1: p },
+        Curve::Quad { p0, p1, .. } => Curve::Quad { p0, p1, p2: p },
+    }
+}
+
+impl Curve {
+    /// Report all defects of the contours, compared exactly.
+    pub(crate) fn validate_segments(segments: &[Segment]) -> Result<(), Vec<PathError>> {
+        let mut errors = Vec::new();
+        for (contour, segment) in segments.iter().enumerate() {
+            for (i, curve) in segment.iter().enumerate() {
+                if !is_finite(curve) {
+                    errors.push(PathError::NonFinite { contour, curve: i });
+                } else if is_degenerate(curve, 0.0) {
+                    errors.push(PathError::ZeroLength { contour, curve: i });
+                }
+                if i > 0 && segment[i - 1].end() != curve.start() {
+                    errors.push(PathError::Disconnected { contour, curve: i });
+                }
+            }
+
+            if let (Some(first), Some(last)) = (segment.first(), segment.last()) {
+                if last.end() != first.start() {
+                    errors.push(PathError::Unclosed { contour });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Clean up contours for rasterization, see `Path::normalize`.
+    pub(crate) fn normalize_segments(segments: &[Segment], options: &NormalizeOptions) -> Vec<Segment> {
+        let tolerance = options.tolerance;
+        segments
+            .iter()
+            .filter(|segment| segment.iter().all(is_finite))
+            .filter_map(|segment| {
+                let mut curves: Vec<Curve> = Vec::with_capacity(segment.len() + 1);
+                for curve in segment.iter().filter(|curve| !is_degenerate(curve, tolerance)) {
+                    let curve = match curves.last() {
+                        Some(last) if (last.end() - curve.start()).length() <= tolerance => with_start(curve, last.end()),
+                        Some(last) => {
+                            let bridge = Curve::Line {
+                                p0: last.end(),
+                                p1: curve.start(),
+                            };
+                            curves.push(bridge);
+                            *curve
+                        }
+                        None => *curve,
+                    };
+                    match curves.last().and_then(|last| merge_lines(last, &curve, tolerance)) {
+                        Some(merged) if options.merge_collinear => *curves.last_mut().unwrap() = merged,
+                        _ => curves.push(curve),
+                    }
+                }
+
+                let start = curves.first()?.start();
+                let end = curves.last()?.end();
+                if (end - start).length() <= tolerance {
+                    let last = curves.pop()?;
+                    curves.push(with_end(&last, start));
+                } else if options.close {
+                    let line = Curve::Line { p0: end, p1: start };
+                    match curves.last().and_then(|last| merge_lines(last, &line, tolerance)) {
+                        Some(merged) if options.merge_collinear => *curves.last_mut().unwrap() = merged,
+                        _ => curves.push(line),
+                    }
+                }
+
+                // Collinear lines across the start of a closed contour.
+                if options.merge_collinear && curves.len() > 2 && curves[curves.len() - 1].end() == curves[0].start() {
+                    if let Some(merged) = merge_lines(&curves[curves.len() - 1], &curves[0], tolerance) {
+                        curves[0] = merged;
+                        curves.pop();
+                    }
+                }
+
+                Some(curves)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PathBuilder;
+
+    #[test]
+    fn validate() {
+        let closed = PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(10.0, 0.0))
+            .line_to(glam::vec2(10.0, 10.0))
+            .close()
+            .finish();
+        assert_eq!(Curve::validate_segments(std::slice::from_ref(&closed)), Ok(()));
+
+        let open = PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(10.0, 0.0))
+            .line_to(glam::vec2(10.0, 0.0))
+            .quad_to(glam::vec2(f32::NAN, 0.0), glam::vec2(10.0, 10.0))
+            .finish();
+        assert_eq!(
+            Curve::validate_segments(&[closed, open]),
+            Err(vec![
+                PathError::ZeroLength { contour: 1, curve: 1 },
+                PathError::NonFinite { contour: 1, curve: 2 },
+                PathError::Unclosed { contour: 1 },
+            ])
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        // Open square with a split edge, a zero length line and a tiny gap.
+        let segment = vec![
+            Curve::Line {
+                p0: glam::vec2(0.0, 0.0),
+                p1: glam::vec2(5.0, 0.0),
+            },
+            Curve::Line {
+                p0: glam::vec2(5.0, 0.0),
+                p1: glam::vec2(10.0, 0.0),
+            },
+            Curve::Line {
+                p0: glam::vec2(10.0, 0.0),
+                p1: glam::vec2(10.0, 0.0),
+            },
+            Curve::Line {
+                p0: glam::vec2(10.0, 0.00001),
+                p1: glam::vec2(10.0, 10.0),
+            },
+            Curve::Line {
+                p0: glam::vec2(10.0, 10.0),
+                p1: glam::vec2(0.0, 10.0),
+            },
+            Curve::Line {
+                p0: glam::vec2(0.0, 10.0),
+                p1: glam::vec2(0.0, 5.0),
+            },
+        ];
+        let invalid = vec![Curve::Line {
+            p0: glam::vec2(0.0, 0.0),
+            p1: glam::vec2(f32::INFINITY, 0.0),
+        }];
+
+        let normalized = Curve::normalize_segments(&[segment.clone(), invalid], &NormalizeOptions::default());
+        assert_eq!(normalized.len(), 1);
+        assert_eq!(Curve::validate_segments(&normalized), Ok(()));
+        // Closing line merged with the last edge.
+        assert_eq!(normalized[0].len(), 4);
+        let area: f32 = normalized[0].iter().map(Curve::signed_area).sum();
+        assert_eq!(area.abs(), 100.0);
+
+        let options = NormalizeOptions {
+            close: false,
+            merge_collinear: false,
+            ..NormalizeOptions::default()
+        };
+        let normalized = Curve::normalize_segments(&[segment], &options);
+        assert_eq!(normalized[0].len(), 5);
+        assert_eq!(
+            Curve::validate_segments(&normalized),
+            Err(vec![PathError::Unclosed { contour: 0 }])
+        );
+    }
+}
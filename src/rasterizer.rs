@@ -3,20 +3,32 @@ mod distance;
 mod analytic_box;
 mod hati;
 mod gouache;
+mod record;
 
 pub use coarse::*;
 pub use distance::*;
 pub use analytic_box::*;
 pub use hati::*;
 pub use gouache::*;
+pub use record::*;
 
 use crate::{Curve, Extent, FillRect, Framebuffer, Offset, Rect, Segment};
 
 pub trait Rasterizer {
+    /// Description of the rasterizer including its configuration.
+    ///
+    /// Rasterizers with equal names are expected to produce the same coverage,
+    /// `GlyphCache` shares cached glyphs between them.
     fn name(&self) -> String;
 
     fn create_path(&mut self, segments: &[Segment]) -> Vec<Curve>;
 
+    /// Blend `value` into the samples covered by the rectangle.
+    ///
+    /// With a single sample per pixel, coverage is the overlap of a pixel sized box around
+    /// the sample with the rectangle, matching `cmd_draw` with a box filter. Multiple samples
+    /// are already antialiased by the resolve, each one is point tested against the half-open
+    /// rectangle instead. Samples outside stay untouched.
     fn cmd_fill(
         &mut self,
         framebuffer: &mut Framebuffer,
@@ -24,15 +36,36 @@ pub trait Rasterizer {
         extent: Extent,
         value: f32,
     ) {
-        let fill_rect = FillRect::new(offset, extent, framebuffer.width, framebuffer.height);
+        let (p0, p1) = (offset.min(offset + extent), offset.max(offset + extent));
+        let fill_rect = FillRect::new_with_bias((1.0, 1.0), p0, p1 - p0, framebuffer.width, framebuffer.height);
         let width = framebuffer.width;
         let num_samples = framebuffer.sample_pos.len();
+        let overlap = |s: f32, e0: f32, e1: f32| {
+            if num_samples == 1 {
+                ((s + 0.5).min(e1) - (s - 0.5).max(e0)).max(0.0)
+            } else if e0 <= s && s < e1 {
+                1.0
+            } else {
+                0.0
+            }
+        };
 
         for y in fill_rect.y0..=fill_rect.y1 {
             for x in fill_rect.x0..=fill_rect.x1 {
                 for sample_id in 0..num_samples {
+                    let pos = glam::vec2(x as f32, y as f32) + framebuffer.sample_pos(x, y, sample_id);
+                    let coverage = overlap(pos.x(), p0.x(), p1.x()) * overlap(pos.y(), p0.y(), p1.y());
+                    if coverage <= 0.0 {
+                        continue;
+                    }
+
                     let i = sample_id + num_samples * (y * width + x) as usize;
-                    framebuffer.samples[i as usize] = value;
+                    let sample = &mut framebuffer.samples[i];
+                    *sample = if coverage < 1.0 {
+                        *sample + (value - *sample) * coverage
+                    } else {
+                        value
+                    };
                 }
             }
         }
@@ -62,10 +95,11 @@ pub(crate) fn rasterize_each_with_bias<F>(
 
     for y in fill_rect.y0..=fill_rect.y1 {
         for x in fill_rect.x0..=fill_rect.x1 {
-            for (sample_id, sample_pos) in framebuffer.sample_pos.iter().enumerate() {
+            for sample_id in 0..num_samples {
                 // if x == 270 && y == 5
                 {
-                    let pos_local = glam::Vec2::new(x as f32, y as f32) + *sample_pos;
+                    let sample_pos = framebuffer.sample_pos(x, y, sample_id);
+                    let pos_local = glam::Vec2::new(x as f32, y as f32) + sample_pos;
                     let pos_curve = rect.local_to_curve(pos_local);
 
                     let i = sample_id + num_samples * (y * width + x) as usize;
@@ -75,3 +109,55 @@ pub(crate) fn rasterize_each_with_bias<F>(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoxFilter, HatiRasterizer, MsaaSampleCount, MsaaSampler, Sampler};
+
+    #[test]
+    fn fill_fractional() {
+        let mut rasterizer = HatiRasterizer {
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(64, 8);
+        framebuffer.add_sample_pos(glam::vec2(0.5, 0.5));
+        let row = |framebuffer: &Framebuffer, y: u32| framebuffer.samples[(y * 64) as usize..((y + 1) * 64) as usize].to_vec();
+
+        rasterizer.cmd_fill(&mut framebuffer, glam::vec2(50.3, 2.0), glam::vec2(3.0, 4.0), 1.0);
+        let coverage = row(&framebuffer, 3);
+        assert_eq!(coverage[49], 0.0);
+        assert!((coverage[50] - 0.7).abs() < 1e-5);
+        assert_eq!(coverage[51..53], [1.0, 1.0]);
+        assert!((coverage[53] - 0.3).abs() < 1e-5);
+        assert_eq!(coverage[54], 0.0);
+        assert!(row(&framebuffer, 1).iter().chain(&row(&framebuffer, 6)).all(|c| *c == 0.0));
+        assert!((framebuffer.samples.iter().sum::<f32>() - 12.0).abs() < 1e-4);
+
+        // Adjacent bands with integer edges don't overlap, partial coverage blends over the background.
+        let mut framebuffer = Framebuffer::new(64, 8);
+        framebuffer.add_sample_pos(glam::vec2(0.5, 0.5));
+        rasterizer.cmd_fill(&mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(3.0, 8.0), 0.2);
+        rasterizer.cmd_fill(&mut framebuffer, glam::vec2(3.0, 0.0), glam::vec2(3.0, 8.0), 0.4);
+        rasterizer.cmd_fill(&mut framebuffer, glam::vec2(6.5, 0.0), glam::vec2(-0.5, 8.0), 1.0);
+        let coverage = row(&framebuffer, 0);
+        assert_eq!(coverage[..6], [0.2, 0.2, 0.2, 0.4, 0.4, 0.4]);
+        assert!((coverage[6] - 0.5).abs() < 1e-6);
+        assert_eq!(coverage[7], 0.0);
+
+        // Multisampled framebuffers get binary sample coverage, antialiasing is left to the resolve.
+        let mut framebuffer = Framebuffer::new(64, 8);
+        MsaaSampler::new(MsaaSampleCount::X4).populate(&mut framebuffer);
+        rasterizer.cmd_fill(&mut framebuffer, glam::vec2(50.3, 2.0), glam::vec2(3.0, 4.0), 1.0);
+        assert!(framebuffer.samples.iter().all(|c| *c == 0.0 || *c == 1.0));
+        let pixel = |framebuffer: &Framebuffer, x: u32, y: u32| {
+            let offset = 4 * (y * 64 + x) as usize;
+            framebuffer.samples[offset..offset + 4].iter().sum::<f32>() / 4.0
+        };
+        let resolved = (49..55).map(|x| pixel(&framebuffer, x, 3)).collect::<Vec<_>>();
+        assert_eq!(resolved, [0.0, 0.75, 1.0, 1.0, 0.25, 0.0]);
+        assert_eq!(pixel(&framebuffer, 51, 1), 0.0);
+        assert_eq!(pixel(&framebuffer, 51, 6), 0.0);
+        assert_eq!(framebuffer.samples.iter().sum::<f32>() / 4.0, 12.0);
+    }
+}
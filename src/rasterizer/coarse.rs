@@ -3,6 +3,7 @@ use crate::{
     Segment, Filter,
 };
 
+#[derive(Debug)]
 pub enum CoarseDirection {
     Y,
     X,
@@ -16,7 +17,7 @@ pub struct CoarseRasterizer<F: Filter> {
 
 impl<F: Filter> Rasterizer for CoarseRasterizer<F> {
     fn name(&self) -> String {
-        format!("CoarseRasterizer :: {}", self.filter.name())
+        format!("CoarseRasterizer {:?} :: {}", self.direction, self.filter.name())
     }
 
     fn create_path(&mut self, segments: &[Segment]) -> Vec<Curve> {
@@ -22,7 +22,7 @@ fn cardano(p: f32, q: f32) -> glam::Vec3 {
     if d > 0.0 {
         let a = 2.0 * (-p / 3.0).sqrt();
         let b = ((27.0 / (-p3)).sqrt() * (-q / 2.0)).acos() / 3.0;
-        glam::vec3(a * b.cos(), a * (b + 2.0 * PI / 3.0), a * (b + 4.0 * PI / 3.0))
+        glam::vec3(a * b.cos(), a * (b + 2.0 * PI / 3.0).cos(), a * (b + 4.0 * PI / 3.0).cos())
     } else if d < 0.0 {
         let coeff = 1.0 / 3.0;
         let dd = (-d / 27.0).sqrt();
@@ -42,20 +42,23 @@ fn cardano(p: f32, q: f32) -> glam::Vec3 {
     }
 }
 
-fn distance_quadratic(p: glam::Vec2, p0: glam::Vec2, p1: glam::Vec2, p2: glam::Vec2) -> f32 {
+/// Candidate curve parameters (unclamped) of the closest point on a quadratic curve to `p`.
+pub(crate) fn closest_quadratic(p: glam::Vec2, p0: glam::Vec2, p1: glam::Vec2, p2: glam::Vec2) -> glam::Vec3 {
     let a = p1 - p0;
     let b = p2 - p1 - a;
 
     let m = p0 - p;
 
-    let t = {
-        let (a, b, c, d) = (b.dot(b), 3.0 * a.dot(b), a.dot(a) * 2.0 + m.dot(b), m.dot(a));
-        let unpress = b / (3.0 * a);
-        let A = glam::vec3(b, c, d) / a;
-        let p = A.y() - A.x()*A.x()/3.0;
-        let q = A.x()*(2.0*A.x()*A.x()-9.0*A.y())/27.0+A.z();
-        cardano(p, q) - glam::vec3(unpress, unpress, unpress)
-    };
+    let (a, b, c, d) = (b.dot(b), 3.0 * a.dot(b), a.dot(a) * 2.0 + m.dot(b), m.dot(a));
+    let unpress = b / (3.0 * a);
+    let A = glam::vec3(b, c, d) / a;
+    let p = A.y() - A.x()*A.x()/3.0;
+    let q = A.x()*(2.0*A.x()*A.x()-9.0*A.y())/27.0+A.z();
+    cardano(p, q) - glam::vec3(unpress, unpress, unpress)
+}
+
+fn distance_quadratic(p: glam::Vec2, p0: glam::Vec2, p1: glam::Vec2, p2: glam::Vec2) -> f32 {
+    let t = closest_quadratic(p, p0, p1, p2);
 
     let mut t_min = t.x().min(1.0).max(0.0);
     let mut n = p - Curve::Quad { p0, p1, p2 }.eval(t_min);
@@ -146,3 +149,26 @@ impl<F: Filter> Rasterizer for DistanceRasterizer<F> {
         );
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn closest_quadratic_roots() {
+        // Parabola y = x² for x in [-1, 1], the point (0, 1) has three stationary distances
+        // at x = 0 and x = ±1/√2.
+        let (p0, p1, p2) = (glam::vec2(-1.0, 1.0), glam::vec2(0.0, -1.0), glam::vec2(1.0, 1.0));
+        let t = closest_quadratic(glam::vec2(0.0, 1.0), p0, p1, p2);
+
+        let mut roots = [t.x(), t.y(), t.z()];
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let offset = 0.5 / std::f32::consts::SQRT_2;
+        for (root, expected) in roots.iter().zip(&[0.5 - offset, 0.5, 0.5 + offset]) {
+            assert!((root - expected).abs() < 1e-4, "{:?}", roots);
+        }
+
+        let d = distance_quadratic(glam::vec2(0.0, 1.0), p0, p1, p2);
+        assert!((d.abs() - 0.75f32.sqrt()).abs() < 1e-4);
+    }
+}
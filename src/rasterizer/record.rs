@@ -0,0 +1,172 @@
+use crate::{hit_test, Curve, Extent, FillRule, Framebuffer, Offset, Rasterizer, Rect, Segment};
+
+/// Draw command captured by `DrawRecorder`.
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    Fill { offset: Offset, extent: Extent, value: f32 },
+    Draw { rect: Rect, path: Vec<Curve>, fill_rule: FillRule },
+}
+
+impl DrawCommand {
+    /// Fill without visible shape: covering the whole `width`x`height` target or blending towards zero.
+    pub fn is_clear(&self, width: u32, height: u32) -> bool {
+        match *self {
+            DrawCommand::Fill { offset, extent, value } => {
+                let (p0, p1) = (offset.min(offset + extent), offset.max(offset + extent));
+                value == 0.0
+                    || (p0.x() <= 0.0 && p0.y() <= 0.0 && p1.x() >= width as f32 && p1.y() >= height as f32)
+            }
+            DrawCommand::Draw { .. } => false,
+        }
+    }
+
+    /// Check if the command covers the point in framebuffer coordinates.
+    pub fn hit_test(&self, point: glam::Vec2) -> bool {
+        let inside = |offset: Offset, extent: Extent| {
+            let (p0, p1) = (offset.min(offset + extent), offset.max(offset + extent));
+            p0.x() <= point.x() && point.x() < p1.x() && p0.y() <= point.y() && point.y() < p1.y()
+        };
+
+        match self {
+            DrawCommand::Fill { offset, extent, .. } => inside(*offset, *extent),
+            DrawCommand::Draw { rect, path, fill_rule } => {
+                inside(rect.offset_local, rect.extent_local)
+                    && hit_test(std::slice::from_ref(path), rect.local_to_curve(point), *fill_rule)
+            }
+        }
+    }
+}
+
+/// Rasterizer forwarding all commands while recording them for later queries.
+///
+/// Only commands targeting framebuffers of the target size `width`x`height` are recorded,
+/// drawing into differently sized scratch buffers (e.g. glyph tiles) is ignored.
+/// Coverage blended directly into the framebuffer without a command, like glyphs
+/// composited by `GlyphCache`, isn't recorded either.
+pub struct DrawRecorder<'a> {
+    pub rasterizer: &'a mut dyn Rasterizer,
+    pub commands: Vec<DrawCommand>,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> DrawRecorder<'a> {
+    pub fn new(rasterizer: &'a mut dyn Rasterizer, width: u32, height: u32) -> Self {
+        DrawRecorder {
+            rasterizer,
+            commands: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    fn record(&mut self, framebuffer: &Framebuffer, command: DrawCommand) {
+        if (framebuffer.width, framebuffer.height) == (self.width, self.height) {
+            self.commands.push(command);
+        }
+    }
+
+    pub fn finish(self) -> Vec<DrawCommand> {
+        self.commands
+    }
+}
+
+impl<'a> Rasterizer for DrawRecorder<'a> {
+    fn name(&self) -> String {
+        self.rasterizer.name()
+    }
+
+    fn create_path(&mut self, segments: &[Segment]) -> Vec<Curve> {
+        self.rasterizer.create_path(segments)
+    }
+
+    fn cmd_fill(&mut self, framebuffer: &mut Framebuffer, offset: Offset, extent: Extent, value: f32) {
+        self.record(framebuffer, DrawCommand::Fill { offset, extent, value });
+        self.rasterizer.cmd_fill(framebuffer, offset, extent, value);
+    }
+
+    fn cmd_draw(&mut self, framebuffer: &mut Framebuffer, rect: Rect, path: &[Curve]) {
+        self.record(
+            framebuffer,
+            DrawCommand::Draw {
+                rect,
+                path: path.to_vec(),
+                // Rasterizers fill paths with the non-zero rule.
+                fill_rule: FillRule::NonZero,
+            },
+        );
+        self.rasterizer.cmd_draw(framebuffer, rect, path);
+    }
+}
+
+/// Index of the last recorded command accepted by `filter` covering `point`.
+pub fn topmost_command<F>(commands: &[DrawCommand], point: glam::Vec2, filter: F) -> Option<usize>
+where
+    F: Fn(&DrawCommand) -> bool,
+{
+    commands.iter().rposition(|command| filter(command) && command.hit_test(point))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoxFilter, HatiRasterizer, PathBuilder};
+
+    #[test]
+    fn topmost() {
+        let mut rasterizer = HatiRasterizer {
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(64, 64);
+        let triangle = vec![PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(0.0, 1.0))
+            .line_to(glam::vec2(1.0, 1.0))
+            .close()
+            .finish()];
+
+        let mut recorder = DrawRecorder::new(&mut rasterizer, 64, 64);
+        // Scratch buffers are not recorded, even when drawn into first.
+        recorder.cmd_fill(&mut Framebuffer::new(8, 8), glam::vec2(0.0, 0.0), glam::vec2(8.0, 8.0), 1.0);
+        recorder.cmd_fill(&mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(32.0, 32.0), 0.5);
+        let path = recorder.create_path(&triangle);
+        let rect = Rect {
+            offset_local: glam::vec2(16.0, 16.0),
+            extent_local: glam::vec2(32.0, 32.0),
+            offset_curve: glam::vec2(0.0, 0.0),
+            extent_curve: glam::vec2(1.0, 1.0),
+        };
+        recorder.cmd_draw(&mut framebuffer, rect, &path);
+        recorder.cmd_draw(&mut Framebuffer::new(8, 8), rect, &path);
+        let commands = recorder.finish();
+        assert_eq!(commands.len(), 2);
+
+        let topmost = |x, y| topmost_command(&commands, glam::vec2(x, y), |_| true);
+        assert_eq!(topmost(4.0, 4.0), Some(0));
+        assert_eq!(topmost(20.0, 40.0), Some(1));
+        // Inside the rect but above the diagonal.
+        assert_eq!(topmost(40.0, 20.0), None);
+        assert_eq!(topmost(24.0, 20.0), Some(0));
+        assert_eq!(topmost(60.0, 60.0), None);
+    }
+
+    #[test]
+    fn skip_clears() {
+        let mut rasterizer = HatiRasterizer {
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(64, 64);
+        let mut recorder = DrawRecorder::new(&mut rasterizer, 64, 64);
+        recorder.cmd_fill(&mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(64.0, 64.0), 0.2);
+        recorder.cmd_fill(&mut framebuffer, glam::vec2(8.0, 8.0), glam::vec2(16.0, 16.0), 1.0);
+        recorder.cmd_fill(&mut framebuffer, glam::vec2(16.0, 16.0), glam::vec2(32.0, 32.0), 0.0);
+        let commands = recorder.finish();
+        assert_eq!(commands.iter().filter(|command| command.is_clear(64, 64)).count(), 2);
+
+        let topmost = |x, y| topmost_command(&commands, glam::vec2(x, y), |command| !command.is_clear(64, 64));
+        assert_eq!(topmost(12.0, 12.0), Some(1));
+        assert_eq!(topmost(20.0, 20.0), Some(1));
+        assert_eq!(topmost(40.0, 40.0), None);
+        assert_eq!(topmost_command(&commands, glam::vec2(40.0, 40.0), |_| true), Some(2));
+    }
+}
@@ -1,5 +1,37 @@
+mod jitter;
+mod msaa;
 mod uniform;
 
+pub use self::jitter::*;
+pub use self::msaa::*;
 pub use self::uniform::*;
 
+use crate::Framebuffer;
+
 pub type SampleId = usize;
+
+pub trait Sampler {
+    /// Register the sample positions of a single pixel.
+    fn populate(&self, framebuffer: &mut Framebuffer);
+
+    /// Register the sample positions for the `pass`-th frame of progressive rendering.
+    ///
+    /// Pass `0` matches `populate`, each pass is reconstructed at its own positions
+    /// before being averaged.
+    fn populate_pass(&self, framebuffer: &mut Framebuffer, pass: usize) {
+        let _ = pass;
+        self.populate(framebuffer);
+    }
+}
+
+// R2 sequence constants, based on the plastic number.
+const R2_A1: f32 = 0.754_877_7;
+const R2_A2: f32 = 0.569_840_3;
+
+/// Element `n` of the R2 low discrepancy sequence, starting at the pixel center.
+pub fn r2_sequence(n: usize) -> glam::Vec2 {
+    glam::vec2(
+        (0.5 + n as f32 * R2_A1).fract(),
+        (0.5 + n as f32 * R2_A2).fract(),
+    )
+}
@@ -0,0 +1,155 @@
+//! Per-pixel variation of the shared sample pattern.
+//!
+//! The offsets rotate the sample pattern of each pixel (Cranley-Patterson rotation),
+//! positions are wrapped back into the `[0,1]²` pixel.
+
+use crate::{r2_sequence, Framebuffer, Sampler};
+
+#[derive(Debug, Clone)]
+pub enum SampleJitter {
+    /// All pixels share the same sample positions.
+    None,
+    /// Hash based white noise rotation per pixel.
+    Scramble { seed: u32 },
+    /// Offset texture repeated over the framebuffer.
+    Tiled {
+        width: u32,
+        height: u32,
+        offsets: Vec<glam::Vec2>,
+    },
+}
+
+impl SampleJitter {
+    /// Tiled blue noise offsets of `size`x`size` pixels.
+    ///
+    /// Pixels are ranked by best-candidate sampling on the torus, the rank is
+    /// mapped onto the R2 sequence to obtain well distributed 2d offsets.
+    pub fn blue_noise(size: u32, seed: u32) -> Self {
+        let num_texels = (size * size) as usize;
+        let max_candidates = 16;
+
+        let mut unranked = (0..num_texels).collect::<Vec<_>>();
+        let mut ranked = Vec::with_capacity(num_texels);
+        let mut offsets = vec![glam::vec2(0.0, 0.0); num_texels];
+        let mut state = hash(seed);
+
+        let toroidal_dist_sq = |a: usize, b: usize| {
+            let (ax, ay) = (a as u32 % size, a as u32 / size);
+            let (bx, by) = (b as u32 % size, b as u32 / size);
+            let dx = ax.max(bx) - ax.min(bx);
+            let dy = ay.max(by) - ay.min(by);
+            let dx = dx.min(size - dx);
+            let dy = dy.min(size - dy);
+            dx * dx + dy * dy
+        };
+
+        for rank in 0..num_texels {
+            let num_candidates = (ranked.len() + 1).min(max_candidates).min(unranked.len());
+
+            let mut best = 0;
+            let mut best_dist = 0;
+            for _ in 0..num_candidates {
+                state = hash(state);
+                let candidate = state as usize % unranked.len();
+                let dist = ranked
+                    .iter()
+                    .map(|&texel| toroidal_dist_sq(unranked[candidate], texel))
+                    .min()
+                    .unwrap_or(u32::MAX);
+                if dist >= best_dist {
+                    best = candidate;
+                    best_dist = dist;
+                }
+            }
+
+            let texel = unranked.swap_remove(best);
+            ranked.push(texel);
+            offsets[texel] = r2_sequence(rank);
+        }
+
+        SampleJitter::Tiled {
+            width: size,
+            height: size,
+            offsets,
+        }
+    }
+
+    /// Offset of the sample pattern for the pixel at `(x, y)`.
+    pub fn offset(&self, x: u32, y: u32) -> glam::Vec2 {
+        match self {
+            SampleJitter::None => glam::vec2(0.0, 0.0),
+            SampleJitter::Scramble { seed } => {
+                let h0 = hash(x ^ hash(y ^ hash(*seed)));
+                let h1 = hash(h0);
+                glam::vec2(unorm(h0), unorm(h1))
+            }
+            SampleJitter::Tiled {
+                width,
+                height,
+                offsets,
+            } => offsets[((y % height) * width + x % width) as usize],
+        }
+    }
+}
+
+/// Sampler wrapper applying a per-pixel jitter on top of the base pattern.
+pub struct JitteredSampler<S: Sampler> {
+    pub sampler: S,
+    pub jitter: SampleJitter,
+}
+
+impl<S: Sampler> Sampler for JitteredSampler<S> {
+    fn populate(&self, framebuffer: &mut Framebuffer) {
+        self.sampler.populate(framebuffer);
+        framebuffer.jitter = self.jitter.clone();
+    }
+
+    fn populate_pass(&self, framebuffer: &mut Framebuffer, pass: usize) {
+        self.sampler.populate_pass(framebuffer, pass);
+        framebuffer.jitter = self.jitter.clone();
+    }
+}
+
+// Integer hash, `lowbias32` by Chris Wellons.
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+fn unorm(x: u32) -> f32 {
+    (x >> 8) as f32 / (1 << 24) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blue_noise_ranks_every_texel() {
+        let size = 8;
+        if let SampleJitter::Tiled { offsets, .. } = SampleJitter::blue_noise(size, 0) {
+            let mut xs = offsets.iter().map(|o| o.x()).collect::<Vec<_>>();
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            xs.dedup();
+            assert_eq!(xs.len(), (size * size) as usize);
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn offsets_inside_pixel() {
+        let jitter = SampleJitter::Scramble { seed: 7 };
+        for y in 0..16 {
+            for x in 0..16 {
+                let offset = jitter.offset(x, y);
+                assert!(offset.x() >= 0.0 && offset.x() < 1.0);
+                assert!(offset.y() >= 0.0 && offset.y() < 1.0);
+            }
+        }
+    }
+}
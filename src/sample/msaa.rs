@@ -0,0 +1,137 @@
+//! Standard multisample patterns as specified by D3D11 and Vulkan.
+//!
+//! Positions are given on the 1/16 pixel grid relative to the pixel center,
+//! with y pointing down.
+
+use crate::{r2_sequence, Framebuffer, Sampler};
+
+const PATTERN_2X: [(i32, i32); 2] = [(4, 4), (-4, -4)];
+
+const PATTERN_4X: [(i32, i32); 4] = [(-2, -6), (6, -2), (-6, 2), (2, 6)];
+
+const PATTERN_8X: [(i32, i32); 8] = [
+    (1, -3),
+    (-1, 3),
+    (5, 1),
+    (-3, -5),
+    (-5, 5),
+    (-7, -1),
+    (3, 7),
+    (7, -7),
+];
+
+const PATTERN_16X: [(i32, i32); 16] = [
+    (1, 1),
+    (-1, -3),
+    (-3, 2),
+    (4, -1),
+    (-5, -2),
+    (2, 5),
+    (5, 3),
+    (3, -5),
+    (-2, 6),
+    (0, -7),
+    (-4, -6),
+    (-6, 4),
+    (-8, 0),
+    (7, -4),
+    (6, 7),
+    (-7, -8),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsaaSampleCount {
+    X2,
+    X4,
+    X8,
+    X16,
+}
+
+pub struct MsaaSampler {
+    pub count: MsaaSampleCount,
+}
+
+impl MsaaSampler {
+    pub fn new(count: MsaaSampleCount) -> Self {
+        MsaaSampler { count }
+    }
+
+    fn pattern(&self) -> &'static [(i32, i32)] {
+        match self.count {
+            MsaaSampleCount::X2 => &PATTERN_2X,
+            MsaaSampleCount::X4 => &PATTERN_4X,
+            MsaaSampleCount::X8 => &PATTERN_8X,
+            MsaaSampleCount::X16 => &PATTERN_16X,
+        }
+    }
+
+    /// Sample positions in `[0,1]²` pixel space.
+    pub fn positions(&self) -> Vec<glam::Vec2> {
+        self.pattern()
+            .iter()
+            .map(|&(x, y)| glam::vec2((x + 8) as f32 / 16.0, (y + 8) as f32 / 16.0))
+            .collect()
+    }
+}
+
+impl Sampler for MsaaSampler {
+    fn populate(&self, framebuffer: &mut Framebuffer) {
+        self.populate_pass(framebuffer, 0);
+    }
+
+    /// Each pass rotates the pattern inside the pixel by the R2 sequence, relative to the first pass.
+    fn populate_pass(&self, framebuffer: &mut Framebuffer, pass: usize) {
+        // Shifted into positive range before wrapping.
+        let offset = r2_sequence(pass) - r2_sequence(0) + glam::vec2(1.0, 1.0);
+        for pos in self.positions() {
+            let pos = pos + offset;
+            framebuffer.add_sample_pos(glam::vec2(pos.x().fract(), pos.y().fract()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn positions_inside_pixel() {
+        for &count in &[MsaaSampleCount::X2, MsaaSampleCount::X4, MsaaSampleCount::X8, MsaaSampleCount::X16] {
+            for pos in MsaaSampler::new(count).positions() {
+                assert!(pos.x() >= 0.0 && pos.x() < 1.0);
+                assert!(pos.y() >= 0.0 && pos.y() < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn pattern_4x() {
+        assert_eq!(
+            MsaaSampler::new(MsaaSampleCount::X4).positions(),
+            vec![
+                glam::vec2(0.375, 0.125),
+                glam::vec2(0.875, 0.375),
+                glam::vec2(0.125, 0.625),
+                glam::vec2(0.625, 0.875),
+            ]
+        );
+    }
+
+    #[test]
+    fn passes() {
+        let sampler = MsaaSampler::new(MsaaSampleCount::X4);
+        let positions = |pass| {
+            let mut framebuffer = Framebuffer::new(1, 1);
+            sampler.populate_pass(&mut framebuffer, pass);
+            framebuffer.sample_pos
+        };
+
+        assert_eq!(positions(0), sampler.positions());
+        assert_ne!(positions(1), positions(0));
+        assert_ne!(positions(2), positions(1));
+        for pos in positions(1).into_iter().chain(positions(2)) {
+            assert!(pos.x() >= 0.0 && pos.x() < 1.0);
+            assert!(pos.y() >= 0.0 && pos.y() < 1.0);
+        }
+    }
+}
@@ -1,19 +1,25 @@
-use crate::{Framebuffer};
+use crate::{r2_sequence, Framebuffer, Sampler};
 
 pub struct UniformSampler {
     pub nx: usize,
     pub ny: usize,
 }
 
-impl UniformSampler {
-    pub fn populate(&self, framebuffer: &mut Framebuffer) {
+impl Sampler for UniformSampler {
+    fn populate(&self, framebuffer: &mut Framebuffer) {
+        self.populate_pass(framebuffer, 0);
+    }
+
+    /// Each pass places the samples at a different position inside their grid cell.
+    fn populate_pass(&self, framebuffer: &mut Framebuffer, pass: usize) {
         let dx = 1.0 / self.nx as f32;
         let dy = 1.0 / self.ny as f32;
+        let offset = r2_sequence(pass);
 
         for y in 0..self.ny {
             for x in 0..self.nx {
-                framebuffer.add_sample_pos(glam::vec2((x as f32 + 0.5) * dx, (y as f32 + 0.5) * dy));
+                framebuffer.add_sample_pos(glam::vec2((x as f32 + offset.x()) * dx, (y as f32 + offset.y()) * dy));
             }
         }
     }
-}
\ No newline at end of file
+}
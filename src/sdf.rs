@@ -0,0 +1,559 @@
+//! Signed distance field (SDF) and multi-channel signed distance field (MSDF) generation.
+//!
+//! MSDF generation follows msdfgen (Chlumsky): contours are split at corners, the edges
+//! colored such that neighboring edges at corners share at most one channel and each channel
+//! stores the pseudo-distance to the closest edge of its color.
+//!
+//! Fields of multiple shapes (e.g. the glyphs of a font) are packed into a shared texture
+//! by `DistanceFieldAtlas`.
+
+use crate::{atlas::ShelfPacker, closest_quadratic, math::*, Aabb, Curve, Framebuffer, Rect, Segment};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Minimal turn angle (sine) between adjacent edges treated as corner.
+const CORNER_THRESHOLD: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceFieldKind {
+    /// Single channel signed distance.
+    Sdf,
+    /// Three channel signed pseudo-distance, median reconstructs sharp corners.
+    Msdf,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DistanceFieldParams {
+    pub kind: DistanceFieldKind,
+    /// Width of the encoded distance range in pixels, centered around the outline.
+    pub range: f32,
+    /// Empty border around the shape in pixels.
+    pub padding: u32,
+}
+
+/// Distance field bitmap, values in `[0, 1]` with the outline at 0.5 and the inside above.
+pub struct DistanceField {
+    pub width: u32,
+    pub height: u32,
+    pub channels: usize,
+    pub data: Vec<f32>,
+    /// Pixel position of the curve space origin.
+    pub origin: glam::Vec2,
+}
+
+// Channel masks
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const MAGENTA: u8 = RED | BLUE;
+const YELLOW: u8 = RED | GREEN;
+
+struct Edge {
+    curve: Curve,
+    color: u8,
+}
+
+struct Closest {
+    distance: f32,
+    /// Orthogonality for resolving ties at shared endpoints.
+    orthogonality: f32,
+    pseudo: f32,
+}
+
+impl DistanceField {
+    /// Generate a `width` x `height` field with `rect` mapping the curves onto the bitmap.
+    pub fn generate(segments: &[Segment], rect: Rect, width: u32, height: u32, params: &DistanceFieldParams) -> Self {
+        let scale = glam::vec2(
+            rect.extent_local.x() / rect.extent_curve.x(),
+            rect.extent_local.y() / rect.extent_curve.y(),
+        );
+        let to_local = |p: glam::Vec2| rect.offset_local + (p - rect.offset_curve) * scale;
+
+        let contours = Curve::orient_segments(segments)
+            .iter()
+            .map(|segment| segment.iter().map(|curve| transform(curve, &to_local)).collect::<Vec<_>>())
+            .filter(|contour: &Vec<Curve>| !contour.is_empty())
+            .collect::<Vec<_>>();
+        let monotone = contours.iter().flat_map(|contour| Curve::monotize_path(contour)).collect::<Vec<_>>();
+
+        let channels = match params.kind {
+            DistanceFieldKind::Sdf => 1,
+            DistanceFieldKind::Msdf => 3,
+        };
+        let edges = match params.kind {
+            DistanceFieldKind::Sdf => contours
+                .iter()
+                .flatten()
+                .map(|curve| Edge { curve: *curve, color: WHITE })
+                .collect::<Vec<_>>(),
+            DistanceFieldKind::Msdf => contours.iter().flat_map(|contour| color_edges(contour)).collect(),
+        };
+
+        let encode = |distance: f32| clamp(distance / params.range + 0.5, 0.0, 1.0);
+
+        let mut data = Vec::with_capacity((width * height) as usize * channels);
+        for y in 0..height {
+            for x in 0..width {
+                let p = glam::vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let inside = winding(&monotone, p) != 0;
+
+                let distance = edges
+                    .iter()
+                    .map(|edge| closest(&edge.curve, p).distance)
+                    .fold(f32::MAX, f32::min);
+                let sdf = if inside { distance } else { -distance };
+
+                if channels == 1 {
+                    data.push(encode(sdf));
+                    continue;
+                }
+
+                let mut msdf = [RED, GREEN, BLUE].map(|channel| {
+                    edges
+                        .iter()
+                        .filter(|edge| edge.color & channel != 0)
+                        .map(|edge| closest(&edge.curve, p))
+                        .fold(None, |best: Option<Closest>, candidate| match best {
+                            Some(best) if !is_closer(&candidate, &best) => Some(best),
+                            _ => Some(candidate),
+                        })
+                        .map_or(sdf, |closest| closest.pseudo)
+                });
+
+                // Error correction: the median has to agree with the true inside test.
+                if (median(msdf) > 0.0) != inside || distance == f32::MAX {
+                    msdf = [sdf; 3];
+                }
+                data.extend(msdf.iter().map(|d| encode(*d)));
+            }
+        }
+
+        DistanceField {
+            width,
+            height,
+            channels,
+            data,
+            origin: to_local(glam::vec2(0.0, 0.0)),
+        }
+    }
+
+    /// Generate a field tightly enclosing the shape scaled by `scale` (pixels per curve unit).
+    pub fn fit(segments: &[Segment], scale: f32, params: &DistanceFieldParams) -> Self {
+        let aabb = Aabb::from_segments(segments);
+        if aabb.is_empty() {
+            return Self::generate(segments, unit_rect(), 0, 0, params);
+        }
+        let (min, max) = (aabb.min, aabb.max);
+
+        let padding = params.padding as f32;
+        let extent = (max - min) * scale;
+        let width = (extent.x().ceil() + 2.0 * padding) as u32;
+        let height = (extent.y().ceil() + 2.0 * padding) as u32;
+        let rect = Rect {
+            offset_local: glam::vec2(padding, padding),
+            extent_local: glam::vec2(scale, scale),
+            offset_curve: min,
+            extent_curve: glam::vec2(1.0, 1.0),
+        };
+
+        Self::generate(segments, rect, width, height, params)
+    }
+
+    pub fn get(&self, x: u32, y: u32, channel: usize) -> f32 {
+        self.data[self.channels * (y * self.width + x) as usize + channel]
+    }
+
+    /// Decoded distance field value, the median of the channels for MSDF.
+    pub fn value(&self, x: u32, y: u32) -> f32 {
+        match self.channels {
+            3 => median([self.get(x, y, 0), self.get(x, y, 1), self.get(x, y, 2)]),
+            _ => self.get(x, y, 0),
+        }
+    }
+
+    /// Single sample framebuffer holding the decoded field.
+    pub fn to_framebuffer(&self) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(self.width, self.height);
+        framebuffer.add_sample_pos(glam::vec2(0.5, 0.5));
+        for y in 0..self.height {
+            for x in 0..self.width {
+                framebuffer.samples[(y * self.width + x) as usize] = self.value(x, y);
+            }
+        }
+        framebuffer
+    }
+
+    /// Quantized channel values, row major and interleaved.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.data.iter().map(|v| (v * 255.0).round() as u8).collect()
+    }
+
+    /// Write the field as binary PGM (SDF) or PPM (MSDF) image.
+    pub fn write_pnm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let magic = if self.channels == 3 { "P6" } else { "P5" };
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        write!(file, "{}\n{} {}\n255\n", magic, self.width, self.height)?;
+        file.write_all(&self.to_bytes())?;
+        file.flush()
+    }
+}
+
+/// Placement of a distance field inside a `DistanceFieldAtlas`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Pixel position of the curve space origin relative to the region.
+    pub origin: glam::Vec2,
+}
+
+/// Distance fields packed into a single texture, using the shelf packing of `GlyphCache`.
+pub struct DistanceFieldAtlas {
+    /// Packed fields, texels outside of any region are fully outside (0).
+    pub field: DistanceField,
+    packer: ShelfPacker,
+}
+
+impl DistanceFieldAtlas {
+    pub fn new(width: u32, height: u32, kind: DistanceFieldKind) -> Self {
+        let channels = match kind {
+            DistanceFieldKind::Sdf => 1,
+            DistanceFieldKind::Msdf => 3,
+        };
+        DistanceFieldAtlas {
+            field: DistanceField {
+                width,
+                height,
+                channels,
+                data: vec![0.0; (width * height) as usize * channels],
+                origin: glam::vec2(0.0, 0.0),
+            },
+            packer: ShelfPacker::new(width, height),
+        }
+    }
+
+    /// Remove all fields.
+    pub fn clear(&mut self) {
+        self.packer.clear();
+        for value in &mut self.field.data {
+            *value = 0.0;
+        }
+    }
+
+    /// Copy `field` into the atlas, `None` if there is no space left.
+    ///
+    /// The field has to match the kind of the atlas.
+    pub fn insert(&mut self, field: &DistanceField) -> Option<AtlasRegion> {
+        assert_eq!(field.channels, self.field.channels);
+
+        let (_, x, y) = self.packer.pack(field.width, field.height, 0)?;
+        let channels = field.channels;
+        for row in 0..field.height {
+            let src = channels * (row * field.width) as usize;
+            let dst = channels * ((y + row) * self.field.width + x) as usize;
+            let len = channels * field.width as usize;
+            self.field.data[dst..dst + len].copy_from_slice(&field.data[src..src + len]);
+        }
+
+        Some(AtlasRegion {
+            x,
+            y,
+            width: field.width,
+            height: field.height,
+            origin: field.origin,
+        })
+    }
+}
+
+#[cfg(feature = "text")]
+impl crate::Glyph {
+    /// Distance field of the glyph at `size` pixels per em, `origin` of the field is the baseline origin.
+    pub fn distance_field(&self, size: f32, params: &DistanceFieldParams) -> DistanceField {
+        DistanceField::fit(&self.segments, size / self.units_per_em, params)
+    }
+}
+
+fn unit_rect() -> Rect {
+    Rect {
+        offset_local: glam::vec2(0.0, 0.0),
+        extent_local: glam::vec2(1.0, 1.0),
+        offset_curve: glam::vec2(0.0, 0.0),
+        extent_curve: glam::vec2(1.0, 1.0),
+    }
+}
+
+fn median([a, b, c]: [f32; 3]) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+fn transform(curve: &Curve, f: &dyn Fn(glam::Vec2) -> glam::Vec2) -> Curve {
+    match *curve {
+        Curve::Line { p0, p1 } => Curve::Line { p0: f(p0), p1: f(p1) },
+        Curve::Quad { p0, p1, p2 } => Curve::Quad {
+            p0: f(p0),
+            p1: f(p1),
+            p2: f(p2),
+        },
+    }
+}
+
+/// Tangent direction at `t`, falling back to the chord for degenerate control points.
+fn direction(curve: &Curve, t: f32) -> glam::Vec2 {
+    let d = curve.derivative(t);
+    if d.length_squared() > 0.0 {
+        d
+    } else {
+        curve.end() - curve.start()
+    }
+}
+
+/// Assign channel colors to the edges of a closed contour (msdfgen's simple edge coloring).
+fn color_edges(contour: &[Curve]) -> Vec<Edge> {
+    let n = contour.len();
+    let corners = (0..n)
+        .filter(|&i| {
+            let a = direction(&contour[(i + n - 1) % n], 1.0).normalize();
+            let b = direction(&contour[i], 0.0).normalize();
+            a.dot(b) <= 0.0 || a.perp_dot(b).abs() > CORNER_THRESHOLD
+        })
+        .collect::<Vec<_>>();
+
+    match corners.len() {
+        // Smooth contour
+        0 => contour.iter().map(|curve| Edge { curve: *curve, color: WHITE }).collect(),
+        // Teardrop, spread three colors along the contour starting at the corner.
+        1 => {
+            let mut curves = (0..n).map(|i| contour[(corners[0] + i) % n]).collect::<Vec<_>>();
+            while curves.len() < 3 {
+                let (a, b) = curves[0].split_at(0.5);
+                curves.splice(0..1, vec![a, b]);
+            }
+            let m = curves.len();
+            curves
+                .into_iter()
+                .enumerate()
+                .map(|(i, curve)| Edge {
+                    curve,
+                    color: [MAGENTA, WHITE, YELLOW][3 * i / m],
+                })
+                .collect()
+        }
+        // Switch colors at each corner, the last spline must differ from the first.
+        num_splines => {
+            let mut edges = Vec::with_capacity(n);
+            for (spline, &corner) in corners.iter().enumerate() {
+                let next = corners[(spline + 1) % num_splines];
+                let color = if spline == num_splines - 1 && spline % 3 == 0 {
+                    MAGENTA
+                } else {
+                    [CYAN, MAGENTA, YELLOW][spline % 3]
+                };
+
+                let len = (next + n - corner - 1) % n + 1;
+                for i in 0..len {
+                    edges.push(Edge {
+                        curve: contour[(corner + i) % n],
+                        color,
+                    });
+                }
+            }
+            edges
+        }
+    }
+}
+
+/// Closest point query, the pseudo-distance is signed with the inside being positive.
+fn closest(curve: &Curve, p: glam::Vec2) -> Closest {
+    let t = match *curve {
+        Curve::Line { p0, p1 } => {
+            let d = p1 - p0;
+            if d.length_squared() > 0.0 {
+                (p - p0).dot(d) / d.length_squared()
+            } else {
+                0.0
+            }
+        }
+        Curve::Quad { p0, p1, p2 } => {
+            let candidates = closest_quadratic(p, p0, p1, p2);
+            [candidates.x(), candidates.y(), candidates.z(), 0.0, 1.0]
+                .iter()
+                .filter(|t| t.is_finite())
+                .map(|t| clamp(*t, 0.0, 1.0))
+                .fold((0.0, f32::MAX), |(best, distance), t| {
+                    let d = (curve.eval(t) - p).length();
+                    if d < distance {
+                        (t, d)
+                    } else {
+                        (best, distance)
+                    }
+                })
+                .0
+        }
+    };
+    let t = clamp(t, 0.0, 1.0);
+
+    let q = curve.eval(t);
+    let dir = direction(curve, t).normalize();
+    let offset = p - q;
+    let distance = offset.length();
+    // Contours are oriented with the inside on the right hand side.
+    let side = if dir.perp_dot(offset) > 0.0 { -1.0 } else { 1.0 };
+
+    // Extend the edge along the end tangents beyond its endpoints.
+    let pseudo = if t <= 0.0 && (p - curve.start()).dot(direction(curve, 0.0)) < 0.0 {
+        -direction(curve, 0.0).normalize().perp_dot(p - curve.start())
+    } else if t >= 1.0 && (p - curve.end()).dot(direction(curve, 1.0)) > 0.0 {
+        -direction(curve, 1.0).normalize().perp_dot(p - curve.end())
+    } else {
+        side * distance
+    };
+
+    Closest {
+        distance,
+        orthogonality: if distance > 0.0 { dir.perp_dot(offset / distance).abs() } else { 1.0 },
+        pseudo: if pseudo.abs() <= distance || !pseudo.is_finite() { pseudo } else { side * distance },
+    }
+}
+
+fn is_closer(a: &Closest, b: &Closest) -> bool {
+    const EPSILON: f32 = 1e-4;
+    if (a.distance - b.distance).abs() <= EPSILON {
+        a.orthogonality > b.orthogonality
+    } else {
+        a.distance < b.distance
+    }
+}
+
+/// Nonzero winding number of monotone curves around `p`.
+fn winding(curves: &[Curve], p: glam::Vec2) -> i32 {
+    curves
+        .iter()
+        .map(|curve| {
+            let (p0, p1) = (curve.start(), curve.end());
+            if (p0.y() <= p.y()) == (p1.y() <= p.y()) {
+                return 0;
+            }
+
+            let x = match *curve {
+                Curve::Line { p0, p1 } => line_eval(p0.x(), p1.x(), line_raycast(p0.y(), p1.y(), p.y())),
+                Curve::Quad { p0, p1, p2 } => quad_eval(p0.x(), p1.x(), p2.x(), quad_raycast(p0.y(), p1.y(), p2.y(), p.y())),
+            };
+            if x > p.x() {
+                if p1.y() > p0.y() {
+                    1
+                } else {
+                    -1
+                }
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PathBuilder;
+
+    fn square() -> Vec<Segment> {
+        vec![PathBuilder::new()
+            .move_to(glam::vec2(0.0, 0.0))
+            .line_to(glam::vec2(10.0, 0.0))
+            .line_to(glam::vec2(10.0, 10.0))
+            .line_to(glam::vec2(0.0, 10.0))
+            .close()
+            .finish()]
+    }
+
+    #[test]
+    fn sdf_square() {
+        let params = DistanceFieldParams {
+            kind: DistanceFieldKind::Sdf,
+            range: 4.0,
+            padding: 3,
+        };
+        let field = DistanceField::fit(&square(), 1.0, &params);
+        assert_eq!((field.width, field.height), (16, 16));
+        assert_eq!(field.origin, glam::vec2(3.0, 3.0));
+
+        // 0.5 pixels inside/outside of the edge
+        assert_eq!(field.value(3, 8), 0.5 + 0.5 / 4.0);
+        assert_eq!(field.value(2, 8), 0.5 - 0.5 / 4.0);
+        assert_eq!(field.value(8, 8), 1.0);
+        assert_eq!(field.value(0, 0), 0.0);
+    }
+
+    #[test]
+    fn msdf_corners() {
+        let contour = square().remove(0);
+        let edges = color_edges(&contour);
+        assert_eq!(edges.len(), 4);
+        for i in 0..4 {
+            // Edges meeting at a corner share a single channel.
+            let shared = edges[i].color & edges[(i + 1) % 4].color;
+            assert_eq!(shared.count_ones(), 1);
+        }
+
+        let params = DistanceFieldParams {
+            kind: DistanceFieldKind::Msdf,
+            range: 4.0,
+            padding: 3,
+        };
+        let msdf = DistanceField::fit(&square(), 1.0, &params);
+        let sdf = DistanceField::fit(&square(), 1.0, &DistanceFieldParams { kind: DistanceFieldKind::Sdf, ..params });
+        assert_eq!(msdf.channels, 3);
+        for y in 0..msdf.height {
+            for x in 0..msdf.width {
+                assert_eq!(msdf.value(x, y) > 0.5, sdf.value(x, y) > 0.5);
+            }
+        }
+
+        // Outside of the corner the median keeps the pseudo-distance, preserving the sharp corner.
+        assert!(msdf.value(2, 2) > sdf.value(2, 2));
+    }
+
+    #[test]
+    fn atlas_packing() {
+        let params = DistanceFieldParams {
+            kind: DistanceFieldKind::Msdf,
+            range: 4.0,
+            padding: 3,
+        };
+        let small = DistanceField::fit(&square(), 1.0, &params);
+        let large = DistanceField::fit(&square(), 2.0, &params);
+
+        let mut atlas = DistanceFieldAtlas::new(48, 48, DistanceFieldKind::Msdf);
+        let regions = [&small, &large, &small, &small]
+            .iter()
+            .map(|field| atlas.insert(field).unwrap())
+            .collect::<Vec<_>>();
+
+        for (i, a) in regions.iter().enumerate() {
+            assert!(a.x + a.width <= 48 && a.y + a.height <= 48);
+            for b in &regions[i + 1..] {
+                let disjoint = a.x + a.width <= b.x || b.x + b.width <= a.x || a.y + a.height <= b.y || b.y + b.height <= a.y;
+                assert!(disjoint, "{:?} {:?}", a, b);
+            }
+        }
+
+        let region = regions[1];
+        assert_eq!(region.origin, large.origin);
+        for y in 0..large.height {
+            for x in 0..large.width {
+                for c in 0..3 {
+                    assert_eq!(atlas.field.get(region.x + x, region.y + y, c), large.get(x, y, c));
+                }
+            }
+        }
+
+        // Out of space.
+        assert!(atlas.insert(&large).is_none());
+        atlas.clear();
+        assert_eq!(atlas.insert(&large).map(|region| (region.x, region.y)), Some((0, 0)));
+    }
+}
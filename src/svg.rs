@@ -0,0 +1,7 @@
+#[cfg(feature = "svg")]
+mod document;
+mod path_data;
+
+#[cfg(feature = "svg")]
+pub use self::document::*;
+pub use self::path_data::*;
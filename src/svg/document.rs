@@ -0,0 +1,906 @@
+//! Minimal SVG document loading and rendering.
+//!
+//! Supports a static subset: `svg`, `g`, `path`, `rect`, `circle`, `ellipse`,
+//! `polygon` and `polyline` with transforms, fills and strokes.
+//! Framebuffers are single channel, paints are reduced to their luminance.
+//!
+//! Unsupported paints (gradients, patterns, unknown colors) are replaced by their fallback
+//! color or `none` and reported in `SvgDocument::warnings`, only malformed values are errors.
+//! Strokes always use round joins and butt caps, other `stroke-linejoin` and `stroke-linecap`
+//! values are reported as well.
+
+use crate::{
+    math::*, parse_svg_path, path_boolean, Curve, Extent, FillRect, FillRule, Framebuffer,
+    NormalizeOptions, Offset, Orientation, PathBuilder, PathOp, Rasterizer, Rect, Segment, SvgPathError,
+};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SvgError {
+    Io(std::io::Error),
+    Xml(roxmltree::Error),
+    Path(SvgPathError),
+    InvalidAttribute { name: String, value: String },
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgError::Io(err) => write!(f, "failed to read document: {}", err),
+            SvgError::Xml(err) => write!(f, "malformed document: {}", err),
+            SvgError::Path(err) => write!(f, "malformed path data: {}", err),
+            SvgError::InvalidAttribute { name, value } => {
+                write!(f, "invalid value '{}' for attribute '{}'", value, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SvgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SvgError::Io(err) => Some(err),
+            SvgError::Xml(err) => Some(err),
+            SvgError::Path(err) => Some(err),
+            SvgError::InvalidAttribute { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SvgError {
+    fn from(err: std::io::Error) -> Self {
+        SvgError::Io(err)
+    }
+}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(err: roxmltree::Error) -> Self {
+        SvgError::Xml(err)
+    }
+}
+
+impl From<SvgPathError> for SvgError {
+    fn from(err: SvgPathError) -> Self {
+        SvgError::Path(err)
+    }
+}
+
+/// 2d affine transformation `[a b c d e f]` as used by SVG.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub m: [f32; 6],
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform { m: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0] }
+    }
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Transform { m: [1.0, 0.0, 0.0, 1.0, tx, ty] }
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Transform { m: [sx, 0.0, 0.0, sy, 0.0, 0.0] }
+    }
+
+    /// Rotation by `angle` degrees.
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Transform { m: [cos, sin, -sin, cos, 0.0, 0.0] }
+    }
+
+    pub fn skew_x(angle: f32) -> Self {
+        Transform { m: [1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0] }
+    }
+
+    pub fn skew_y(angle: f32) -> Self {
+        Transform { m: [1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0] }
+    }
+
+    /// Combined transform, applying `other` first.
+    pub fn then(&self, other: &Transform) -> Self {
+        let [a0, b0, c0, d0, e0, f0] = self.m;
+        let [a1, b1, c1, d1, e1, f1] = other.m;
+        Transform {
+            m: [
+                a0 * a1 + c0 * b1,
+                b0 * a1 + d0 * b1,
+                a0 * c1 + c0 * d1,
+                b0 * c1 + d0 * d1,
+                a0 * e1 + c0 * f1 + e0,
+                b0 * e1 + d0 * f1 + f0,
+            ],
+        }
+    }
+
+    pub fn apply(&self, p: glam::Vec2) -> glam::Vec2 {
+        let [a, b, c, d, e, f] = self.m;
+        glam::vec2(a * p.x() + c * p.y() + e, b * p.x() + d * p.y() + f)
+    }
+
+    pub fn apply_curve(&self, curve: &Curve) -> Curve {
+        match *curve {
+            Curve::Line { p0, p1 } => Curve::Line {
+                p0: self.apply(p0),
+                p1: self.apply(p1),
+            },
+            Curve::Quad { p0, p1, p2 } => Curve::Quad {
+                p0: self.apply(p0),
+                p1: self.apply(p1),
+                p2: self.apply(p2),
+            },
+        }
+    }
+}
+
+/// Presentation attributes, inherited by child elements.
+#[derive(Debug, Clone)]
+pub struct SvgStyle {
+    /// Fill luminance, `None` if not filled.
+    pub fill: Option<f32>,
+    pub fill_opacity: f32,
+    pub fill_rule: FillRule,
+    /// Stroke luminance, `None` if not stroked.
+    pub stroke: Option<f32>,
+    pub stroke_opacity: f32,
+    pub stroke_width: f32,
+    pub opacity: f32,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        SvgStyle {
+            fill: Some(0.0),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+pub struct SvgShape {
+    /// Fill outline in viewBox coordinates, filled with the non-zero rule.
+    ///
+    /// One segment per subpath, even-odd fills are resolved into non-overlapping contours.
+    pub segments: Vec<Segment>,
+    /// Stroke outline in viewBox coordinates, filled with the non-zero rule.
+    pub stroke_segments: Vec<Segment>,
+    pub style: SvgStyle,
+}
+
+pub struct SvgDocument {
+    pub view_box_offset: Offset,
+    pub view_box_extent: Extent,
+    pub shapes: Vec<SvgShape>,
+    /// Unsupported features replaced by a fallback while parsing.
+    pub warnings: Vec<String>,
+}
+
+impl SvgDocument {
+    pub fn load<P: AsRef<std::path::Path>>(path: P, tolerance: f32) -> Result<Self, SvgError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, tolerance)
+    }
+
+    /// Parse a document, curves are approximated by quads within `tolerance` (in user units).
+    pub fn parse(text: &str, tolerance: f32) -> Result<Self, SvgError> {
+        let xml = roxmltree::Document::parse(text)?;
+        let root = xml.root_element();
+
+        let (view_box_offset, view_box_extent) = match root.attribute("viewBox") {
+            Some(view_box) => {
+                let values = parse_list("viewBox", view_box)?;
+                if values.len() != 4 {
+                    return Err(invalid_attribute("viewBox", view_box));
+                }
+                (glam::vec2(values[0], values[1]), glam::vec2(values[2], values[3]))
+            }
+            None => {
+                // Percentages need a viewport, there is none to resolve them against.
+                let width = length_attribute(&root, "width", 0.0)?.unwrap_or(0.0);
+                let height = length_attribute(&root, "height", 0.0)?.unwrap_or(0.0);
+                (glam::vec2(0.0, 0.0), glam::vec2(width, height))
+            }
+        };
+
+        let mut document = SvgDocument {
+            view_box_offset,
+            view_box_extent,
+            shapes: Vec::new(),
+            warnings: Vec::new(),
+        };
+        document.parse_children(root, &SvgStyle::default(), &Transform::identity(), tolerance)?;
+
+        Ok(document)
+    }
+
+    fn parse_children(
+        &mut self,
+        node: roxmltree::Node,
+        style: &SvgStyle,
+        transform: &Transform,
+        tolerance: f32,
+    ) -> Result<(), SvgError> {
+        let extent = self.view_box_extent;
+        let diagonal = extent.length() / std::f32::consts::SQRT_2;
+
+        for child in node.children().filter(|child| child.is_element()) {
+            if child.attribute("display") == Some("none") {
+                continue;
+            }
+
+            let style = parse_style(&child, style, self.view_box_extent, &mut self.warnings)?;
+            let transform = match child.attribute("transform") {
+                Some(value) => transform.then(&parse_transform(value)?),
+                None => *transform,
+            };
+
+            let builder = PathBuilder::new().with_tolerance(tolerance);
+            let segments = match child.tag_name().name() {
+                "g" => {
+                    self.parse_children(child, &style, &transform, tolerance)?;
+                    continue;
+                }
+                "path" => parse_svg_path(child.attribute("d").unwrap_or(""), tolerance)?,
+                "rect" => {
+                    let x = length_attribute(&child, "x", extent.x())?.unwrap_or(0.0);
+                    let y = length_attribute(&child, "y", extent.y())?.unwrap_or(0.0);
+                    let w = length_attribute(&child, "width", extent.x())?.unwrap_or(0.0);
+                    let h = length_attribute(&child, "height", extent.y())?.unwrap_or(0.0);
+                    let rx = length_attribute(&child, "rx", extent.x())?;
+                    let ry = length_attribute(&child, "ry", extent.y())?;
+                    let (rx, ry) = match (rx, ry) {
+                        (Some(rx), Some(ry)) => (rx, ry),
+                        (Some(r), None) | (None, Some(r)) => (r, r),
+                        (None, None) => (0.0, 0.0),
+                    };
+                    let rx = rx.min(0.5 * w);
+                    let ry = ry.min(0.5 * h);
+
+                    if w <= 0.0 || h <= 0.0 {
+                        continue;
+                    }
+                    let (offset, extent) = (glam::vec2(x, y), glam::vec2(w, h));
+                    let builder = if rx <= 0.0 || ry <= 0.0 {
+                        builder.rect(offset, extent)
+                    } else if rx == ry {
+                        builder.rounded_rect(offset, extent, [rx; 4])
+                    } else {
+                        // Elliptical corners.
+                        let radii = glam::vec2(rx, ry);
+                        builder
+                            .move_to(glam::vec2(x + rx, y))
+                            .line_to(glam::vec2(x + w - rx, y))
+                            .arc_to(radii, 0.0, false, true, glam::vec2(x + w, y + ry))
+                            .line_to(glam::vec2(x + w, y + h - ry))
+                            .arc_to(radii, 0.0, false, true, glam::vec2(x + w - rx, y + h))
+                            .line_to(glam::vec2(x + rx, y + h))
+                            .arc_to(radii, 0.0, false, true, glam::vec2(x, y + h - ry))
+                            .line_to(glam::vec2(x, y + ry))
+                            .arc_to(radii, 0.0, false, true, glam::vec2(x + rx, y))
+                            .close()
+                    };
+                    builder.finish_path().to_segments()
+                }
+                "circle" | "ellipse" => {
+                    let cx = length_attribute(&child, "cx", extent.x())?.unwrap_or(0.0);
+                    let cy = length_attribute(&child, "cy", extent.y())?.unwrap_or(0.0);
+                    let (rx, ry) = if child.tag_name().name() == "circle" {
+                        let r = length_attribute(&child, "r", diagonal)?.unwrap_or(0.0);
+                        (r, r)
+                    } else {
+                        (
+                            length_attribute(&child, "rx", extent.x())?.unwrap_or(0.0),
+                            length_attribute(&child, "ry", extent.y())?.unwrap_or(0.0),
+                        )
+                    };
+
+                    if rx <= 0.0 || ry <= 0.0 {
+                        continue;
+                    }
+                    builder.ellipse(glam::vec2(cx, cy), glam::vec2(rx, ry)).finish_path().to_segments()
+                }
+                name @ "polygon" | name @ "polyline" => {
+                    let points = child.attribute("points").unwrap_or("");
+                    let values = parse_list("points", points)?;
+                    if values.len() < 4 {
+                        continue;
+                    }
+                    let mut builder = builder.move_to(glam::vec2(values[0], values[1]));
+                    for p in values[2..].chunks(2).filter(|p| p.len() == 2) {
+                        builder = builder.line_to(glam::vec2(p[0], p[1]));
+                    }
+                    if name == "polygon" {
+                        builder = builder.close();
+                    }
+                    builder.finish_path().to_segments()
+                }
+                _ => continue,
+            };
+
+            let stroke_segments = match style.stroke {
+                Some(_) if style.stroke_width > 0.0 => stroke_outline(&segments, 0.5 * style.stroke_width, tolerance),
+                _ => Vec::new(),
+            };
+
+            let apply = |segments: Vec<Segment>| {
+                segments
+                    .iter()
+                    .map(|segment| {
+                        let curves = segment.iter().map(|curve| transform.apply_curve(curve)).collect::<Vec<_>>();
+                        Curve::monotize_path(&curves)
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            // Fills implicitly close open subpaths.
+            let fill_segments = Curve::normalize_segments(&segments, &NormalizeOptions::default());
+            // The rasterizers only implement the non-zero rule, self-intersections need to be resolved for even-odd.
+            let fill_segments = match style.fill_rule {
+                FillRule::EvenOdd if style.fill.is_some() => {
+                    path_boolean(&fill_segments, &[], PathOp::Union, FillRule::EvenOdd)
+                }
+                _ => fill_segments,
+            };
+
+            self.shapes.push(SvgShape {
+                segments: apply(fill_segments),
+                stroke_segments: apply(stroke_segments),
+                style,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Draw the document into the area spanned by `offset` and `extent`.
+    ///
+    /// Each shape is rasterized into a scratch buffer covering the area and
+    /// composited over the current framebuffer content.
+    pub fn render(
+        &self,
+        rasterizer: &mut dyn Rasterizer,
+        framebuffer: &mut Framebuffer,
+        offset: Offset,
+        extent: Extent,
+    ) {
+        let fill_rect = FillRect::new_with_bias((1.0, 1.0), offset, extent, framebuffer.width, framebuffer.height);
+        let mut coverage = framebuffer.region(
+            fill_rect.x0,
+            fill_rect.y0,
+            fill_rect.x1 - fill_rect.x0 + 1,
+            fill_rect.y1 - fill_rect.y0 + 1,
+        );
+        let rect = Rect {
+            offset_local: offset - glam::vec2(fill_rect.x0 as f32, fill_rect.y0 as f32),
+            extent_local: extent,
+            offset_curve: self.view_box_offset,
+            extent_curve: self.view_box_extent,
+        };
+
+        for shape in &self.shapes {
+            let style = &shape.style;
+
+            if let Some(value) = style.fill {
+                draw_coverage(rasterizer, &mut coverage, rect, &shape.segments);
+                composite(framebuffer, &coverage, &fill_rect, value, style.fill_opacity * style.opacity);
+            }
+
+            if let Some(value) = style.stroke {
+                if !shape.stroke_segments.is_empty() {
+                    draw_coverage(rasterizer, &mut coverage, rect, &shape.stroke_segments);
+                    composite(framebuffer, &coverage, &fill_rect, value, style.stroke_opacity * style.opacity);
+                }
+            }
+        }
+    }
+}
+
+/// Rasterize the coverage of the segments (non-zero rule) into `coverage`.
+fn draw_coverage(rasterizer: &mut dyn Rasterizer, coverage: &mut Framebuffer, rect: Rect, segments: &[Segment]) {
+    for sample in &mut coverage.samples {
+        *sample = 0.0;
+    }
+    let path = rasterizer.create_path(&Curve::orient_segments(segments));
+    rasterizer.cmd_draw(coverage, rect, &path);
+}
+
+/// Blend `value` into the framebuffer, weighted by the scratch `coverage` placed at the fill rect.
+fn composite(framebuffer: &mut Framebuffer, coverage: &Framebuffer, fill_rect: &FillRect, value: f32, opacity: f32) {
+    let num_samples = framebuffer.sample_pos.len();
+    for y in 0..coverage.height {
+        for x in 0..coverage.width {
+            let src = num_samples * (y * coverage.width + x) as usize;
+            let dst = num_samples * ((fill_rect.y0 + y) * framebuffer.width + fill_rect.x0 + x) as usize;
+            for i in 0..num_samples {
+                let alpha = clamp(coverage.samples[src + i], 0.0, 1.0) * opacity;
+                let sample = &mut framebuffer.samples[dst + i];
+                *sample += (value - *sample) * alpha;
+            }
+        }
+    }
+}
+
+/// Stroke outline: a rectangle for each flattened segment, round joins and butt caps.
+fn stroke_outline(segments: &[Segment], half_width: f32, tolerance: f32) -> Vec<Segment> {
+    let mut outline = Vec::new();
+
+    for segment in segments {
+        let mut points = Vec::new();
+        for line in Curve::flatten_path(segment, tolerance) {
+            if points.is_empty() {
+                points.push(line.start());
+            }
+            points.push(line.end());
+        }
+        points.dedup();
+
+        let closed = points.len() > 2 && points.first() == points.last();
+
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let dir = (b - a).normalize();
+            let n = glam::vec2(-dir.y(), dir.x()) * half_width;
+            outline.push(vec![
+                Curve::Line { p0: a + n, p1: b + n },
+                Curve::Line { p0: b + n, p1: b - n },
+                Curve::Line { p0: b - n, p1: a - n },
+                Curve::Line { p0: a - n, p1: a + n },
+            ]);
+        }
+
+        let joins = if closed {
+            &points[1..]
+        } else if points.len() > 2 {
+            &points[1..points.len() - 1]
+        } else {
+            &[]
+        };
+        for &p in joins {
+            let join = PathBuilder::new().with_tolerance(tolerance).circle(p, half_width);
+            outline.extend(join.finish_path().to_segments());
+        }
+    }
+
+    // All pieces share the same orientation so overlaps accumulate under the non-zero rule.
+    for segment in &mut outline {
+        if Curve::contour_orientation(segment) == Some(Orientation::Clockwise) {
+            *segment = Curve::reverse_contour(segment);
+        }
+    }
+
+    outline
+}
+
+fn invalid_attribute(name: &str, value: &str) -> SvgError {
+    SvgError::InvalidAttribute {
+        name: name.into(),
+        value: value.into(),
+    }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<f32, SvgError> {
+    let value_trimmed = value.trim();
+    let number = value_trimmed.strip_suffix("px").unwrap_or(value_trimmed);
+    number.parse().map_err(|_| invalid_attribute(name, value))
+}
+
+fn parse_list(name: &str, value: &str) -> Result<Vec<f32>, SvgError> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_number(name, v))
+        .collect()
+}
+
+/// Absolute length units in user units (px), font relative units assume a 16px font.
+const LENGTH_UNITS: [(&str, f32); 8] = [
+    ("px", 1.0),
+    ("pt", 4.0 / 3.0),
+    ("pc", 16.0),
+    ("mm", 96.0 / 25.4),
+    ("cm", 96.0 / 2.54),
+    ("in", 96.0),
+    ("em", 16.0),
+    ("ex", 8.0),
+];
+
+/// Length in user units, percentages are relative to `reference`.
+fn parse_length(name: &str, value: &str, reference: f32) -> Result<f32, SvgError> {
+    let trimmed = value.trim();
+    let (number, scale) = match trimmed.strip_suffix('%') {
+        Some(number) => (number, 0.01 * reference),
+        None => LENGTH_UNITS
+            .iter()
+            .find_map(|&(unit, scale)| Some((trimmed.strip_suffix(unit)?, scale)))
+            .unwrap_or((trimmed, 1.0)),
+    };
+    number
+        .parse::<f32>()
+        .map(|number| number * scale)
+        .map_err(|_| invalid_attribute(name, value))
+}
+
+fn length_attribute(node: &roxmltree::Node, name: &str, reference: f32) -> Result<Option<f32>, SvgError> {
+    node.attribute(name).map(|value| parse_length(name, value, reference)).transpose()
+}
+
+fn parse_transform(value: &str) -> Result<Transform, SvgError> {
+    let mut transform = Transform::identity();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(|| invalid_attribute("transform", value))?;
+        let close = rest.find(')').ok_or_else(|| invalid_attribute("transform", value))?;
+        if close < open {
+            return Err(invalid_attribute("transform", value));
+        }
+
+        let name = rest[..open].trim();
+        let args = parse_list("transform", &rest[open + 1..close])?;
+
+        let t = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform { m: [a, b, c, d, e, f] },
+            ("translate", &[tx]) => Transform::translate(tx, 0.0),
+            ("translate", &[tx, ty]) => Transform::translate(tx, ty),
+            ("scale", &[s]) => Transform::scale(s, s),
+            ("scale", &[sx, sy]) => Transform::scale(sx, sy),
+            ("rotate", &[angle]) => Transform::rotate(angle),
+            ("rotate", &[angle, cx, cy]) => Transform::translate(cx, cy)
+                .then(&Transform::rotate(angle))
+                .then(&Transform::translate(-cx, -cy)),
+            ("skewX", &[angle]) => Transform::skew_x(angle),
+            ("skewY", &[angle]) => Transform::skew_y(angle),
+            _ => return Err(invalid_attribute("transform", value)),
+        };
+        transform = transform.then(&t);
+
+        rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+
+    Ok(transform)
+}
+
+/// Luminance of a paint, `None` for `none`.
+///
+/// Paint servers (`url(...)`) are replaced by their fallback color or `none`, as are unknown
+/// color keywords; both are reported in `warnings`. The alpha of `#rgba`, `#rrggbbaa` and
+/// `rgba()` colors is ignored.
+fn parse_paint(name: &str, value: &str, warnings: &mut Vec<String>) -> Result<Option<f32>, SvgError> {
+    let value = value.trim();
+    let rgb = match value {
+        "none" | "transparent" => return Ok(None),
+        "black" | "currentColor" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "lime" => [0, 255, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "gray" | "grey" => [128, 128, 128],
+        _ if value.starts_with("url(") => {
+            let close = value.find(')').ok_or_else(|| invalid_attribute(name, value))?;
+            let fallback = value[close + 1..].trim();
+            warnings.push(format!("unsupported paint server '{}' for '{}'", &value[..close + 1], name));
+            return match fallback {
+                "" => Ok(None),
+                _ => parse_paint(name, fallback, warnings),
+            };
+        }
+        _ if value.starts_with('#') => {
+            let hex = &value[1..];
+            let channel = |i: usize, len: usize| {
+                u8::from_str_radix(&hex[i..i + len], 16).map_err(|_| invalid_attribute(name, value))
+            };
+            match hex.len() {
+                3 | 4 if hex.is_ascii() => [channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17],
+                6 | 8 if hex.is_ascii() => [channel(0, 2)?, channel(2, 2)?, channel(4, 2)?],
+                _ => return Err(invalid_attribute(name, value)),
+            }
+        }
+        _ if (value.starts_with("rgb(") || value.starts_with("rgba(")) && value.ends_with(')') => {
+            let args = &value[value.find('(').unwrap() + 1..value.len() - 1];
+            let components = args
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>();
+            if components.len() != 3 && components.len() != 4 {
+                return Err(invalid_attribute(name, value));
+            }
+            let channel = |i: usize| -> Result<u8, SvgError> {
+                let c = parse_length(name, components[i], 255.0)?;
+                Ok(clamp(c.round(), 0.0, 255.0) as u8)
+            };
+            [channel(0)?, channel(1)?, channel(2)?]
+        }
+        _ if value.chars().all(|c| c.is_ascii_alphabetic()) => {
+            warnings.push(format!("unsupported color '{}' for '{}'", value, name));
+            return Ok(None);
+        }
+        _ => return Err(invalid_attribute(name, value)),
+    };
+
+    let linear = |c: u8| srgb_to_linear(c as f32 / 255.0);
+    Ok(Some(0.2126 * linear(rgb[0]) + 0.7152 * linear(rgb[1]) + 0.0722 * linear(rgb[2])))
+}
+
+/// Resolve the presentation attributes of `node`, percentages are relative to the viewBox `extent`.
+fn parse_style(node: &roxmltree::Node, parent: &SvgStyle, extent: Extent, warnings: &mut Vec<String>) -> Result<SvgStyle, SvgError> {
+    let mut style = parent.clone();
+    // Opacity isn't inherited but applies to the group as a whole.
+    style.opacity = parent.opacity;
+
+    let declarations = node
+        .attribute("style")
+        .unwrap_or("")
+        .split(';')
+        .filter_map(|declaration| {
+            let mut parts = declaration.splitn(2, ':');
+            Some((parts.next()?.trim(), parts.next()?.trim()))
+        })
+        .collect::<Vec<_>>();
+
+    let properties = [
+        "fill",
+        "fill-opacity",
+        "fill-rule",
+        "stroke",
+        "stroke-opacity",
+        "stroke-width",
+        "stroke-linejoin",
+        "stroke-linecap",
+        "opacity",
+    ];
+    for &property in &properties {
+        // Style declarations take precedence over presentation attributes.
+        let value = declarations
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == property)
+            .map(|(_, value)| *value)
+            .or_else(|| node.attribute(property));
+
+        let value = match value {
+            Some(value) if value != "inherit" => value,
+            _ => continue,
+        };
+
+        match property {
+            "fill" => style.fill = parse_paint(property, value, warnings)?,
+            "stroke" => style.stroke = parse_paint(property, value, warnings)?,
+            "fill-opacity" => style.fill_opacity = clamp(parse_number(property, value)?, 0.0, 1.0),
+            "stroke-opacity" => style.stroke_opacity = clamp(parse_number(property, value)?, 0.0, 1.0),
+            "stroke-width" => {
+                style.stroke_width = parse_length(property, value, extent.length() / std::f32::consts::SQRT_2)?
+            }
+            "opacity" => style.opacity *= clamp(parse_number(property, value)?, 0.0, 1.0),
+            "stroke-linejoin" if value != "round" => {
+                warnings.push(format!("unsupported stroke-linejoin '{}', using round joins", value));
+            }
+            "stroke-linecap" if value != "butt" => {
+                warnings.push(format!("unsupported stroke-linecap '{}', using butt caps", value));
+            }
+            "stroke-linejoin" | "stroke-linecap" => {}
+            "fill-rule" => {
+                style.fill_rule = match value {
+                    "nonzero" => FillRule::NonZero,
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => return Err(invalid_attribute(property, value)),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(style)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transform_list() {
+        let t = parse_transform("translate(10, 20) scale(2)").unwrap();
+        assert_eq!(t.apply(glam::vec2(1.0, 1.0)), glam::vec2(12.0, 22.0));
+
+        let t = parse_transform("rotate(90 5 5)").unwrap();
+        let p = t.apply(glam::vec2(10.0, 5.0));
+        assert!((p - glam::vec2(5.0, 10.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn paints() {
+        let mut warnings = Vec::new();
+        let mut paint = |value| parse_paint("fill", value, &mut warnings);
+        assert_eq!(paint("none").unwrap(), None);
+        assert_eq!(paint("#000").unwrap(), Some(0.0));
+        assert!((paint("#ffffff").unwrap().unwrap() - 1.0).abs() < 1e-5);
+        assert_eq!(paint("#fff8").unwrap(), paint("#ffffff80").unwrap());
+        assert_eq!(paint("rgb(100%, 0%, 0%)").unwrap(), paint("red").unwrap());
+        assert_eq!(paint("rgba(255 0 0 / 0.5)").unwrap(), paint("red").unwrap());
+        assert!(paint("#12").is_err());
+        assert!(paint("rgb(1, 2)").is_err());
+        assert!(paint("url(#gradient").is_err());
+
+        // Unsupported paints fall back with a warning.
+        assert_eq!(paint("url(#gradient)").unwrap(), None);
+        assert_eq!(paint("url(#gradient) white").unwrap(), paint("white").unwrap());
+        assert_eq!(paint("rebeccapurple").unwrap(), None);
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn lengths() {
+        assert_eq!(parse_length("x", "12", 50.0).unwrap(), 12.0);
+        assert_eq!(parse_length("x", " 12px", 50.0).unwrap(), 12.0);
+        assert_eq!(parse_length("x", "1e1", 50.0).unwrap(), 10.0);
+        assert_eq!(parse_length("x", "50%", 50.0).unwrap(), 25.0);
+        assert_eq!(parse_length("x", "1.5em", 50.0).unwrap(), 24.0);
+        assert_eq!(parse_length("x", "1in", 50.0).unwrap(), 96.0);
+        assert!(parse_length("x", "12vw", 50.0).is_err());
+    }
+
+    #[test]
+    fn document() {
+        let document = SvgDocument::parse(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+                <g transform="translate(2 2)" fill="white">
+                    <rect width="10" height="10" rx="2"/>
+                    <circle cx="15" cy="15" r="4" style="fill: #808080; fill-opacity: 0.5"/>
+                </g>
+                <polyline points="0,0 10,10 20,0" fill="none" stroke="white" stroke-width="2"/>
+                <path d="M0 0h4v4h-4z M1 1h2v2h-2z" fill-rule="evenodd"/>
+            </svg>"#,
+            0.01,
+        )
+        .unwrap();
+
+        assert_eq!(document.view_box_extent, glam::vec2(24.0, 24.0));
+        assert_eq!(document.shapes.len(), 4);
+        assert_eq!(document.shapes[0].style.fill, Some(1.0));
+        assert_eq!(document.shapes[1].style.fill_opacity, 0.5);
+        assert!(!document.shapes[2].stroke_segments.is_empty());
+        assert_eq!(document.shapes[3].style.fill_rule, FillRule::EvenOdd);
+        assert_eq!(document.shapes[3].segments.len(), 2);
+    }
+
+    #[test]
+    fn render_evenodd() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        let document = SvgDocument::parse(
+            r#"<svg viewBox="0 0 8 8"><path d="M0 0h8v8h-8z M2 2h4v4h-4z" fill="white" fill-rule="evenodd"/></svg>"#,
+            0.01,
+        )
+        .unwrap();
+
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(8, 8);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+        document.render(&mut rasterizer, &mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(8.0, 8.0));
+
+        assert_eq!(framebuffer.samples[8 + 1], 1.0);
+        assert_eq!(framebuffer.samples[4 * 8 + 4], 0.0);
+    }
+
+    #[test]
+    fn render_evenodd_self_intersecting() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        // Pentagram drawn as a single subpath, the inner pentagon is enclosed twice.
+        let star = |fill_rule| {
+            let document = SvgDocument::parse(
+                &format!(
+                    r#"<svg viewBox="0 0 16 16"><path d="M8 0 L12.70 14.47 L0.39 5.53 L15.61 5.53 L3.30 14.47 Z" fill="white" fill-rule="{}"/></svg>"#,
+                    fill_rule
+                ),
+                0.01,
+            )
+            .unwrap();
+
+            let mut rasterizer = CoarseRasterizer {
+                direction: CoarseDirection::Y,
+                filter: BoxFilter::new(-0.5, 0.5),
+            };
+            let mut framebuffer = Framebuffer::new(16, 16);
+            UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+            document.render(&mut rasterizer, &mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(16.0, 16.0));
+            framebuffer
+        };
+
+        let evenodd = star("evenodd");
+        assert_eq!(evenodd.samples[8 * 16 + 8], 0.0);
+        assert_eq!(evenodd.samples[3 * 16 + 7], 1.0);
+        assert_eq!(evenodd.samples[6 * 16 + 2], 1.0);
+
+        let nonzero = star("nonzero");
+        assert_eq!(nonzero.samples[8 * 16 + 8], 1.0);
+        assert_eq!(nonzero.samples[3 * 16 + 7], 1.0);
+    }
+
+    #[test]
+    fn render_region() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, SampleJitter, UniformSampler};
+
+        let document = SvgDocument::parse(
+            r#"<svg viewBox="0 0 4 4"><rect x="1" y="1" width="2.5" height="2" fill="white"/></svg>"#,
+            0.01,
+        )
+        .unwrap();
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(16, 16);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+        framebuffer.jitter = SampleJitter::Scramble { seed: 3 };
+        for sample in &mut framebuffer.samples {
+            *sample = 0.5;
+        }
+
+        // Samples outside of the document area are untouched, the jitter matches the target pixels.
+        document.render(&mut rasterizer, &mut framebuffer, glam::vec2(8.0, 4.0), glam::vec2(4.0, 4.0));
+        for y in 0..16 {
+            for x in 0..16 {
+                let pos = glam::vec2(x as f32, y as f32) + framebuffer.sample_pos(x, y, 0);
+                let inside = pos.x() >= 9.0 && pos.x() < 11.5 && pos.y() >= 5.0 && pos.y() < 7.0;
+                let expected = if inside { 1.0 } else { 0.5 };
+                assert_eq!(framebuffer.samples[(y * 16 + x) as usize], expected, "pixel {} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn stroke_warnings() {
+        let document = SvgDocument::parse(
+            r#"<svg viewBox="0 0 8 8">
+                <g stroke="white" stroke-linejoin="miter">
+                    <polyline points="0,0 4,4 8,0" stroke-linecap="round" stroke-miterlimit="2"/>
+                    <polyline points="0,8 4,4 8,8" stroke-linejoin="round" stroke-linecap="butt"/>
+                </g>
+            </svg>"#,
+            0.01,
+        )
+        .unwrap();
+        assert_eq!(document.shapes.len(), 2);
+        assert_eq!(document.warnings.len(), 2);
+        assert!(document.warnings[0].contains("stroke-linejoin"));
+        assert!(document.warnings[1].contains("stroke-linecap"));
+    }
+
+    #[test]
+    fn render_unsupported_paint() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        let document = SvgDocument::parse(
+            r#"<svg viewBox="0 0 8 8">
+                <defs><linearGradient id="shade"><stop offset="0" stop-color="white"/></linearGradient></defs>
+                <rect width="50%" height="8" fill="url(#shade)"/>
+                <rect x="4" width="4" height="8" fill="white"/>
+            </svg>"#,
+            0.01,
+        )
+        .unwrap();
+        assert_eq!(document.shapes.len(), 2);
+        assert_eq!(document.warnings.len(), 1);
+
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(8, 8);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+        document.render(&mut rasterizer, &mut framebuffer, glam::vec2(0.0, 0.0), glam::vec2(8.0, 8.0));
+
+        assert_eq!(framebuffer.samples[4 * 8 + 1], 0.0);
+        assert_eq!(framebuffer.samples[4 * 8 + 6], 1.0);
+    }
+}
@@ -0,0 +1,370 @@
+//! SVG path data (`d` attribute) parsing.
+//!
+//! See https://www.w3.org/TR/SVG11/paths.html#PathData for the grammar.
+
+use crate::{PathBuilder, Segment};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgPathError {
+    /// Path data doesn't start with a `moveto` command.
+    MissingMoveTo { pos: usize },
+    UnexpectedChar { pos: usize, ch: char },
+    InvalidNumber { pos: usize },
+    InvalidFlag { pos: usize },
+    UnexpectedEnd,
+}
+
+impl fmt::Display for SvgPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgPathError::MissingMoveTo { pos } => write!(f, "expected moveto command at {}", pos),
+            SvgPathError::UnexpectedChar { pos, ch } => write!(f, "unexpected character '{}' at {}", ch, pos),
+            SvgPathError::InvalidNumber { pos } => write!(f, "invalid number at {}", pos),
+            SvgPathError::InvalidFlag { pos } => write!(f, "invalid arc flag at {}", pos),
+            SvgPathError::UnexpectedEnd => write!(f, "unexpected end of path data"),
+        }
+    }
+}
+
+impl std::error::Error for SvgPathError {}
+
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.data.get(self.pos) {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' | b',' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.data.get(self.pos) {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).cloned()
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos >= self.data.len()
+    }
+
+    /// Check if the next token (after separators) starts a number.
+    fn has_number(&mut self) -> bool {
+        let pos = self.pos;
+        self.skip_separators();
+        let has_number = match self.peek() {
+            Some(c) => c.is_ascii_digit() || c == b'-' || c == b'+' || c == b'.',
+            None => false,
+        };
+        self.pos = pos;
+        has_number
+    }
+
+    fn command(&mut self) -> Result<u8, SvgPathError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                self.pos += 1;
+                Ok(c)
+            }
+            Some(c) => Err(SvgPathError::UnexpectedChar { pos: self.pos, ch: c as char }),
+            None => Err(SvgPathError::UnexpectedEnd),
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, SvgPathError> {
+        self.skip_separators();
+
+        let start = self.pos;
+        let digits = |lexer: &mut Self| {
+            let begin = lexer.pos;
+            while matches!(lexer.peek(), Some(c) if c.is_ascii_digit()) {
+                lexer.pos += 1;
+            }
+            lexer.pos - begin
+        };
+
+        if let Some(b'-') | Some(b'+') = self.peek() {
+            self.pos += 1;
+        }
+        let mut num_digits = digits(self);
+        if let Some(b'.') = self.peek() {
+            self.pos += 1;
+            num_digits += digits(self);
+        }
+        if num_digits == 0 {
+            return match self.data.get(start) {
+                Some(_) => Err(SvgPathError::InvalidNumber { pos: start }),
+                None => Err(SvgPathError::UnexpectedEnd),
+            };
+        }
+
+        // Exponent, `e` must be followed by digits to not swallow a following command.
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if let Some(b'-') | Some(b'+') = self.peek() {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(SvgPathError::InvalidNumber { pos: start })
+    }
+
+    fn flag(&mut self) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Err(SvgPathError::InvalidFlag { pos: self.pos }),
+            None => Err(SvgPathError::UnexpectedEnd),
+        }
+    }
+
+    fn point(&mut self) -> Result<glam::Vec2, SvgPathError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(glam::vec2(x, y))
+    }
+}
+
+/// Parse SVG path data into segments, one per subpath.
+///
+/// Cubics and elliptical arcs are approximated by quads within `tolerance`.
+pub fn parse_svg_path(data: &str, tolerance: f32) -> Result<Vec<Segment>, SvgPathError> {
+    let mut lexer = Lexer { data: data.as_bytes(), pos: 0 };
+    let mut segments = Vec::new();
+
+    let mut path: Option<PathBuilder> = None;
+    let mut start = glam::vec2(0.0, 0.0);
+    let mut current = glam::vec2(0.0, 0.0);
+
+    // Reflected control points for smooth curves.
+    let mut last_cubic_ctrl: Option<glam::Vec2> = None;
+    let mut last_quad_ctrl: Option<glam::Vec2> = None;
+
+    let mut command = None;
+
+    while !lexer.at_end() {
+        // Commands may be repeated implicitly by providing more arguments.
+        let cmd = match command {
+            Some(cmd) if cmd != b'Z' && cmd != b'z' && lexer.has_number() => cmd,
+            _ => lexer.command()?,
+        };
+
+        let relative = cmd.is_ascii_lowercase();
+        let base = if relative { current } else { glam::vec2(0.0, 0.0) };
+
+        if path.is_none() && !matches!(cmd, b'M' | b'm') {
+            if command.is_none() {
+                return Err(SvgPathError::MissingMoveTo { pos: lexer.pos - 1 });
+            }
+            // Continue drawing after `closepath` from the start of the last subpath.
+            path = Some(PathBuilder::new().with_tolerance(tolerance).move_to(start));
+        }
+
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                let p = base + lexer.point()?;
+                if let Some(path) = path.take() {
+                    segments.push(path.finish());
+                }
+                path = Some(PathBuilder::new().with_tolerance(tolerance).move_to(p));
+                start = p;
+                current = p;
+                // Subsequent pairs are implicit `lineto` commands.
+                command = Some(if relative { b'l' } else { b'L' });
+                last_cubic_ctrl = None;
+                last_quad_ctrl = None;
+                continue;
+            }
+            b'L' => {
+                let p = base + lexer.point()?;
+                path = path.map(|path| path.line_to(p));
+                current = p;
+            }
+            b'H' => {
+                let x = lexer.number()?;
+                let p = glam::vec2(if relative { current.x() + x } else { x }, current.y());
+                path = path.map(|path| path.line_to(p));
+                current = p;
+            }
+            b'V' => {
+                let y = lexer.number()?;
+                let p = glam::vec2(current.x(), if relative { current.y() + y } else { y });
+                path = path.map(|path| path.line_to(p));
+                current = p;
+            }
+            b'Q' => {
+                let p1 = base + lexer.point()?;
+                let p2 = base + lexer.point()?;
+                path = path.map(|path| path.quad_to(p1, p2));
+                quad_ctrl = Some(p1);
+                current = p2;
+            }
+            b'T' => {
+                let p1 = last_quad_ctrl.map_or(current, |ctrl| 2.0 * current - ctrl);
+                let p2 = base + lexer.point()?;
+                path = path.map(|path| path.quad_to(p1, p2));
+                quad_ctrl = Some(p1);
+                current = p2;
+            }
+            b'C' => {
+                let p1 = base + lexer.point()?;
+                let p2 = base + lexer.point()?;
+                let p3 = base + lexer.point()?;
+                path = path.map(|path| path.cubic_to(p1, p2, p3));
+                cubic_ctrl = Some(p2);
+                current = p3;
+            }
+            b'S' => {
+                let p1 = last_cubic_ctrl.map_or(current, |ctrl| 2.0 * current - ctrl);
+                let p2 = base + lexer.point()?;
+                let p3 = base + lexer.point()?;
+                path = path.map(|path| path.cubic_to(p1, p2, p3));
+                cubic_ctrl = Some(p2);
+                current = p3;
+            }
+            b'A' => {
+                let rx = lexer.number()?;
+                let ry = lexer.number()?;
+                let rotation = lexer.number()?;
+                let large_arc = lexer.flag()?;
+                let sweep = lexer.flag()?;
+                let p = base + lexer.point()?;
+                path = path.map(|path| path.arc_to(glam::vec2(rx, ry), rotation, large_arc, sweep, p));
+                current = p;
+            }
+            b'Z' => {
+                if let Some(path) = path.take() {
+                    segments.push(path.close().finish());
+                }
+                current = start;
+            }
+            _ => {
+                return Err(SvgPathError::UnexpectedChar {
+                    pos: lexer.pos - 1,
+                    ch: cmd as char,
+                })
+            }
+        }
+
+        last_cubic_ctrl = cubic_ctrl;
+        last_quad_ctrl = quad_ctrl;
+        command = Some(cmd);
+    }
+
+    if let Some(path) = path {
+        segments.push(path.finish());
+    }
+
+    segments.retain(|segment| !segment.is_empty());
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Curve;
+
+    #[test]
+    fn absolute_and_relative() {
+        let abs = parse_svg_path("M10 10 L20 10 L20 20 Z", 0.1).unwrap();
+        let rel = parse_svg_path("m10,10 l10,0 0,10z", 0.1).unwrap();
+        assert_eq!(abs.len(), 1);
+        assert_eq!(abs[0].len(), 3);
+        assert_eq!(rel.len(), 1);
+        for (a, b) in abs[0].iter().zip(&rel[0]) {
+            assert_eq!(a.end(), b.end());
+        }
+    }
+
+    #[test]
+    fn horizontal_vertical() {
+        let segments = parse_svg_path("M0 0H10V10h-10v-10", 0.1).unwrap();
+        let ends = segments[0].iter().map(Curve::end).collect::<Vec<_>>();
+        assert_eq!(
+            ends,
+            vec![
+                glam::vec2(10.0, 0.0),
+                glam::vec2(10.0, 10.0),
+                glam::vec2(0.0, 10.0),
+                glam::vec2(0.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn compact_numbers() {
+        let segments = parse_svg_path("M.5.5l-.5-.5 1e1 0", 0.1).unwrap();
+        let ends = segments[0].iter().map(Curve::end).collect::<Vec<_>>();
+        assert_eq!(ends, vec![glam::vec2(0.0, 0.0), glam::vec2(10.0, 0.0)]);
+    }
+
+    #[test]
+    fn subpaths() {
+        let segments = parse_svg_path("M0 0L1 0L1 1Z M2 2L3 2L3 3Z", 0.1).unwrap();
+        assert_eq!(segments.len(), 2);
+    }
+
+    #[test]
+    fn curves_reach_endpoint() {
+        let segments = parse_svg_path("M0 0C0 10 10 10 10 0S20 -10 20 0A5 5 0 1 1 30 0T40 0", 0.01).unwrap();
+        let ends = segments[0].iter().map(Curve::end).collect::<Vec<_>>();
+        assert!(ends.contains(&glam::vec2(10.0, 0.0)));
+        assert!(ends.contains(&glam::vec2(20.0, 0.0)));
+        assert!(ends.contains(&glam::vec2(30.0, 0.0)));
+        assert_eq!(*ends.last().unwrap(), glam::vec2(40.0, 0.0));
+    }
+
+    #[test]
+    fn arc_within_tolerance() {
+        let tolerance = 0.01;
+        let segments = parse_svg_path("M0 0A10 10 0 0 1 20 0", tolerance).unwrap();
+        for curve in &segments[0] {
+            for i in 0..=8 {
+                let p = curve.eval(i as f32 / 8.0);
+                let r = (p - glam::vec2(10.0, 0.0)).length();
+                assert!((r - 10.0).abs() <= tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(parse_svg_path("L10 10", 0.1).unwrap_err(), SvgPathError::MissingMoveTo { pos: 0 });
+        assert_eq!(parse_svg_path("M10", 0.1).unwrap_err(), SvgPathError::UnexpectedEnd);
+        assert_eq!(parse_svg_path("M10 10 X", 0.1).unwrap_err(), SvgPathError::UnexpectedChar { pos: 7, ch: 'X' });
+        assert_eq!(parse_svg_path("M0 0 A1 1 0 2 0 1 1", 0.1).unwrap_err(), SvgPathError::InvalidFlag { pos: 12 });
+        assert_eq!(parse_svg_path("M0 0 L-. 1", 0.1).unwrap_err(), SvgPathError::InvalidNumber { pos: 6 });
+    }
+}
@@ -0,0 +1,13 @@
+mod cache;
+mod color;
+mod draw;
+mod font;
+mod layout;
+mod options;
+
+pub use self::cache::*;
+pub use self::color::*;
+pub use self::draw::*;
+pub use self::font::*;
+pub use self::layout::*;
+pub use self::options::*;
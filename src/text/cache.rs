@@ -0,0 +1,455 @@
+//! Glyph coverage cache.
+//!
+//! Rasterized glyphs are stored as coverage tiles in a shelf packed atlas and
+//! composited into the framebuffer on subsequent draws. Tiles hold all samples
+//! of a pixel, the cache is flushed when the sample pattern changes.
+
+use crate::{atlas::ShelfPacker, draw_outline, math::clamp, Font, Framebuffer, Glyph, GlyphId, GlyphPosition, Rasterizer, SampleJitter, TextRenderOptions};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Empty border around each tile, rasterizers touch up to one pixel outside the glyph bounds.
+const TILE_PADDING: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    /// User assigned font identifier, distinct for each set of variation coordinates.
+    pub font: u32,
+    pub glyph: GlyphId,
+    /// Size in 1/64 pixels per em.
+    pub size: u32,
+    /// Horizontal subpixel phase.
+    pub subpixel: u32,
+    /// Hash of the rasterizer name, which describes the rasterizer configuration.
+    pub rasterizer: u64,
+}
+
+/// Coverage texture holding the cached tiles, `num_samples` values per texel.
+pub struct Atlas {
+    pub width: u32,
+    pub height: u32,
+    pub num_samples: usize,
+    pub samples: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tile {
+    shelf: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// Offset of the tile relative to the (pixel snapped) glyph origin.
+    offset_x: i32,
+    offset_y: i32,
+}
+
+/// Cache of rasterized glyph tiles.
+///
+/// Eviction works on whole shelves of the atlas: once full, the shelf whose most recently
+/// drawn tile is the oldest is emptied. Other tiles sharing the shelf are dropped with it
+/// and rasterized again on their next draw.
+pub struct GlyphCache {
+    atlas: Atlas,
+    sample_pos: Vec<glam::Vec2>,
+    tiles: HashMap<GlyphKey, Tile>,
+    packer: ShelfPacker,
+    /// Number of horizontal subpixel positions per pixel.
+    subpixel_steps: u32,
+    options: TextRenderOptions,
+    clock: u64,
+}
+
+impl GlyphCache {
+    pub fn new(width: u32, height: u32, subpixel_steps: u32) -> Self {
+        GlyphCache {
+            atlas: Atlas {
+                width,
+                height,
+                num_samples: 0,
+                samples: Vec::new(),
+            },
+            sample_pos: Vec::new(),
+            tiles: HashMap::new(),
+            packer: ShelfPacker::new(width, height),
+            subpixel_steps: subpixel_steps.max(1),
+            options: TextRenderOptions::default(),
+            clock: 0,
+        }
+    }
+
+    pub fn options(&self) -> &TextRenderOptions {
+        &self.options
+    }
+
+    /// Change the glyph appearance, flushes the cache on changes.
+    pub fn set_options(&mut self, options: TextRenderOptions) {
+        if self.options != options {
+            self.options = options;
+            self.clear();
+        }
+    }
+
+    pub fn atlas(&self) -> &Atlas {
+        &self.atlas
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Drop all cached tiles.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.packer.clear();
+        for sample in &mut self.atlas.samples {
+            *sample = 0.0;
+        }
+    }
+
+    /// Composite a glyph with baseline origin at `origin` (in pixels) into the framebuffer.
+    ///
+    /// The origin is snapped to whole pixels vertically and to `subpixel_steps` phases horizontally.
+    /// Coverage is blended towards `value`.
+    ///
+    /// Framebuffers with per-pixel jitter bypass the cache, as the sample positions differ between
+    /// glyph instances. The glyph is rasterized on every call in this case.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        rasterizer: &mut dyn Rasterizer,
+        framebuffer: &mut Framebuffer,
+        font_id: u32,
+        font: &Font,
+        glyph: GlyphId,
+        size: f32,
+        origin: glam::Vec2,
+        value: f32,
+    ) {
+        let position = GlyphPosition::quantize(origin, self.subpixel_steps);
+
+        let key = GlyphKey {
+            font: font_id,
+            glyph,
+            size: (size * 64.0).round() as u32,
+            subpixel: position.phase,
+            rasterizer: {
+                let mut hasher = DefaultHasher::new();
+                rasterizer.name().hash(&mut hasher);
+                hasher.finish()
+            },
+        };
+
+        // Positions varying per pixel can't be shared between glyph instances, draw uncached instead.
+        if !matches!(framebuffer.jitter, SampleJitter::None) {
+            if let Some((coverage, offset_x, offset_y)) = Self::rasterize(rasterizer, framebuffer, font, glyph, size, position, &self.options) {
+                composite(framebuffer, &coverage.samples, coverage.width, coverage.height, 0, 0, coverage.width, position.x + offset_x, position.y + offset_y, value);
+            }
+            return;
+        }
+
+        if self.sample_pos != framebuffer.sample_pos {
+            self.sample_pos = framebuffer.sample_pos.clone();
+            self.atlas.num_samples = self.sample_pos.len();
+            self.atlas.samples = vec![0.0; (self.atlas.width * self.atlas.height) as usize * self.atlas.num_samples];
+            self.tiles.clear();
+            self.packer.clear();
+        }
+
+        self.clock += 1;
+        let tile = match self.tiles.get(&key) {
+            Some(tile) => *tile,
+            None => {
+                let coverage = Self::rasterize(rasterizer, framebuffer, font, glyph, size, position, &self.options);
+                let (coverage, offset_x, offset_y) = match coverage {
+                    Some(coverage) => coverage,
+                    None => return,
+                };
+                match self.insert(key, &coverage, offset_x, offset_y) {
+                    Some(tile) => tile,
+                    None => {
+                        // Glyph exceeds the atlas, draw uncached.
+                        composite(framebuffer, &coverage.samples, coverage.width, coverage.height, 0, 0, coverage.width, position.x + offset_x, position.y + offset_y, value);
+                        return;
+                    }
+                }
+            }
+        };
+        self.packer.shelves[tile.shelf].last_used = self.clock;
+
+        composite(
+            framebuffer,
+            &self.atlas.samples,
+            tile.width,
+            tile.height,
+            tile.x,
+            tile.y,
+            self.atlas.width,
+            position.x + tile.offset_x,
+            position.y + tile.offset_y,
+            value,
+        );
+    }
+
+    fn rasterize(
+        rasterizer: &mut dyn Rasterizer,
+        framebuffer: &Framebuffer,
+        font: &Font,
+        glyph: GlyphId,
+        size: f32,
+        position: GlyphPosition,
+        options: &TextRenderOptions,
+    ) -> Option<(Framebuffer, i32, i32)> {
+        let glyph = font.glyph(glyph).filter(|glyph| !glyph.is_empty())?;
+        let glyph = options.darken(&glyph, size).unwrap_or(glyph);
+        Some(rasterize_tile(rasterizer, framebuffer, &glyph, size, position, options))
+    }
+
+    fn insert(&mut self, key: GlyphKey, coverage: &Framebuffer, offset_x: i32, offset_y: i32) -> Option<Tile> {
+        let (width, height) = (coverage.width, coverage.height);
+        if width > self.atlas.width || height > self.atlas.height {
+            return None;
+        }
+
+        let (shelf, x, y) = match self.packer.pack(width, height, self.clock) {
+            Some(place) => place,
+            None => {
+                self.evict(height);
+                self.packer.pack(width, height, self.clock)?
+            }
+        };
+
+        let num_samples = self.atlas.num_samples;
+        for ty in 0..height {
+            let src = num_samples * (ty * width) as usize;
+            let dst = num_samples * ((y + ty) * self.atlas.width + x) as usize;
+            let len = num_samples * width as usize;
+            self.atlas.samples[dst..dst + len].copy_from_slice(&coverage.samples[src..src + len]);
+        }
+
+        let tile = Tile {
+            shelf,
+            x,
+            y,
+            width,
+            height,
+            offset_x,
+            offset_y,
+        };
+        self.tiles.insert(key, tile);
+        Some(tile)
+    }
+
+    /// Evict the least recently used shelf able to hold a tile of `height`.
+    ///
+    /// Flushes the whole atlas if no shelf is tall enough.
+    fn evict(&mut self, height: u32) {
+        match self.packer.evict(height) {
+            Some(i) => self.tiles.retain(|_, tile| tile.shelf != i),
+            None => self.clear(),
+        }
+    }
+}
+
+/// Rasterize an outline into a padded tile sharing the sample positions of `framebuffer`.
+///
+/// Only the subpixel phase of `position` is considered, returns the tile together
+/// with its offset relative to the glyph origin. The coverage adjustment of `options`
+/// is applied to the tile. With per-pixel jitter the outline is
+/// drawn at `position` into a scratch framebuffer of the full size instead and the tile
+/// is cut out, keeping the sample positions of the destination pixels.
+pub(crate) fn rasterize_tile(
+    rasterizer: &mut dyn Rasterizer,
+    framebuffer: &Framebuffer,
+    outline: &Glyph,
+    size: f32,
+    position: GlyphPosition,
+    options: &TextRenderOptions,
+) -> (Framebuffer, i32, i32) {
+    let scale = size / outline.units_per_em;
+    let subpixel = glam::vec2(position.fraction(), 0.0);
+    let min = outline.bounds.min * scale + subpixel;
+    let max = outline.bounds.max * scale + subpixel;
+
+    let offset_x = min.x().floor() as i32 - TILE_PADDING;
+    let offset_y = min.y().floor() as i32 - TILE_PADDING;
+    let width = (max.x().ceil() as i32 + TILE_PADDING - offset_x) as u32;
+    let height = (max.y().ceil() as i32 + TILE_PADDING - offset_y) as u32;
+
+    let mut tile = Framebuffer::new(width, height);
+    for pos in &framebuffer.sample_pos {
+        tile.add_sample_pos(*pos);
+    }
+
+    if matches!(framebuffer.jitter, SampleJitter::None) {
+        let position = GlyphPosition {
+            x: -offset_x,
+            y: -offset_y,
+            ..position
+        };
+        draw_outline(rasterizer, &mut tile, outline, size, position);
+    } else {
+        let mut scratch = Framebuffer::new(framebuffer.width, framebuffer.height);
+        for pos in &framebuffer.sample_pos {
+            scratch.add_sample_pos(*pos);
+        }
+        scratch.jitter = framebuffer.jitter.clone();
+        draw_outline(rasterizer, &mut scratch, outline, size, position);
+
+        let num_samples = framebuffer.sample_pos.len();
+        for ty in 0..height as i32 {
+            for tx in 0..width as i32 {
+                let (x, y) = (position.x + offset_x + tx, position.y + offset_y + ty);
+                if x < 0 || y < 0 || x >= framebuffer.width as i32 || y >= framebuffer.height as i32 {
+                    continue;
+                }
+                let src = num_samples * (y as u32 * framebuffer.width + x as u32) as usize;
+                let dst = num_samples * (ty as u32 * width + tx as u32) as usize;
+                tile.samples[dst..dst + num_samples].copy_from_slice(&scratch.samples[src..src + num_samples]);
+            }
+        }
+    }
+
+    // Adjust the isolated glyph coverage, before it's blended over other content.
+    if options.adjusts_coverage() {
+        for sample in &mut tile.samples {
+            *sample = options.adjust_coverage(*sample);
+        }
+    }
+
+    (tile, offset_x, offset_y)
+}
+
+/// Blend a coverage tile towards `value` into the framebuffer at pixel `(x, y)`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn composite(
+    framebuffer: &mut Framebuffer,
+    samples: &[f32],
+    width: u32,
+    height: u32,
+    src_x: u32,
+    src_y: u32,
+    src_stride: u32,
+    x: i32,
+    y: i32,
+    value: f32,
+) {
+    let num_samples = framebuffer.sample_pos.len();
+
+    for ty in 0..height as i32 {
+        let dy = y + ty;
+        if dy < 0 || dy >= framebuffer.height as i32 {
+            continue;
+        }
+        for tx in 0..width as i32 {
+            let dx = x + tx;
+            if dx < 0 || dx >= framebuffer.width as i32 {
+                continue;
+            }
+
+            let src = num_samples * ((src_y + ty as u32) * src_stride + src_x + tx as u32) as usize;
+            let dst = num_samples * (dy as u32 * framebuffer.width + dx as u32) as usize;
+            for s in 0..num_samples {
+                let alpha = clamp(samples[src + s], 0.0, 1.0);
+                framebuffer.samples[dst + s] += (value - framebuffer.samples[dst + s]) * alpha;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+    const ROBOTO: &[u8] = include_bytes!("../../assets/Roboto-Regular.ttf");
+
+    fn setup() -> (CoarseRasterizer<BoxFilter>, Framebuffer) {
+        let rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(64, 32);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+        (rasterizer, framebuffer)
+    }
+
+    #[test]
+    fn cached_matches_uncached() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let glyph = font.glyph_id('g').unwrap();
+        let (mut rasterizer, mut framebuffer) = setup();
+        let mut reference = framebuffer.clone();
+
+        let mut cache = GlyphCache::new(64, 64, 4);
+        cache.draw(&mut rasterizer, &mut framebuffer, 0, &font, glyph, 20.0, glam::vec2(4.0, 20.0), 1.0);
+        assert_eq!(cache.len(), 1);
+
+        // Second draw is served from the atlas.
+        cache.draw(&mut rasterizer, &mut framebuffer, 0, &font, glyph, 20.0, glam::vec2(30.0, 20.0), 1.0);
+        assert_eq!(cache.len(), 1);
+
+        // An atlas without space falls back to drawing uncached.
+        let mut uncached = GlyphCache::new(0, 0, 4);
+        uncached.draw(&mut rasterizer, &mut reference, 0, &font, glyph, 20.0, glam::vec2(4.0, 20.0), 1.0);
+        uncached.draw(&mut rasterizer, &mut reference, 0, &font, glyph, 20.0, glam::vec2(30.0, 20.0), 1.0);
+        assert!(uncached.is_empty());
+
+        assert!(framebuffer.samples.iter().any(|&s| s > 0.5));
+        assert_eq!(framebuffer.samples, reference.samples);
+    }
+
+    #[test]
+    fn eviction() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let (mut rasterizer, mut framebuffer) = setup();
+
+        // Room for roughly a single shelf.
+        let mut cache = GlyphCache::new(40, 24, 1);
+        for c in "abcdefghijklmnop".chars() {
+            let glyph = font.glyph_id(c).unwrap();
+            cache.draw(&mut rasterizer, &mut framebuffer, 0, &font, glyph, 16.0, glam::vec2(4.0, 20.0), 1.0);
+        }
+        assert!(cache.len() < 16);
+        assert!(!cache.is_empty());
+    }
+
+    #[test]
+    fn rasterizer_configurations() {
+        use crate::Smoothstep;
+
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let glyph = font.glyph_id('g').unwrap();
+        let (mut box_filter, framebuffer) = setup();
+        let mut smoothstep = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: Smoothstep { e0: -0.5, e1: 0.5 },
+        };
+        let mut wide = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-1.0, 1.0),
+        };
+        let mut xy = CoarseRasterizer {
+            direction: CoarseDirection::XY,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+
+        let mut cache = GlyphCache::new(256, 64, 4);
+        let mut rasterizers: [&mut dyn Rasterizer; 4] = [&mut box_filter, &mut smoothstep, &mut wide, &mut xy];
+        for (i, rasterizer) in rasterizers.iter_mut().enumerate() {
+            let mut cached = framebuffer.clone();
+            cache.draw(&mut **rasterizer, &mut cached, 0, &font, glyph, 20.0, glam::vec2(4.0, 20.0), 1.0);
+            assert_eq!(cache.len(), i + 1);
+
+            // Each configuration gets its own tile, matching the uncached result.
+            let mut reference = framebuffer.clone();
+            GlyphCache::new(0, 0, 4).draw(&mut **rasterizer, &mut reference, 0, &font, glyph, 20.0, glam::vec2(4.0, 20.0), 1.0);
+            assert_eq!(cached.samples, reference.samples);
+        }
+    }
+}
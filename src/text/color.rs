@@ -0,0 +1,175 @@
+//! Layered color glyphs (`COLR` version 0).
+//!
+//! Framebuffers are single channel, layer colors are reduced to their luminance when drawing.
+
+use crate::{composite, math::srgb_to_linear, rasterize_tile, Framebuffer, Glyph, GlyphId, GlyphPosition, Rasterizer, TextRenderOptions};
+
+pub struct ColorLayer {
+    pub glyph: Glyph,
+    /// Palette color as sRGB with alpha, `None` for the text foreground color.
+    pub color: Option<[u8; 4]>,
+}
+
+/// Color glyph made of outlines drawn on top of each other.
+pub struct ColorGlyph {
+    pub id: GlyphId,
+    /// Layers from bottom to top.
+    pub layers: Vec<ColorLayer>,
+}
+
+impl ColorLayer {
+    /// Linear luminance and opacity of the layer.
+    pub fn luminance(&self, foreground: f32) -> (f32, f32) {
+        match self.color {
+            Some([r, g, b, a]) => {
+                let linear = |c: u8| srgb_to_linear(c as f32 / 255.0);
+                (
+                    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b),
+                    a as f32 / 255.0,
+                )
+            }
+            None => (foreground, 1.0),
+        }
+    }
+}
+
+impl ColorGlyph {
+    /// Composite the layers in order at `size` pixels per em.
+    pub fn draw(
+        &self,
+        rasterizer: &mut dyn Rasterizer,
+        framebuffer: &mut Framebuffer,
+        size: f32,
+        position: GlyphPosition,
+        foreground: f32,
+    ) {
+        let options = TextRenderOptions::default();
+        for layer in self.layers.iter().filter(|layer| !layer.glyph.is_empty()) {
+            let (luminance, opacity) = layer.luminance(foreground);
+            let (mut tile, offset_x, offset_y) = rasterize_tile(rasterizer, framebuffer, &layer.glyph, size, position, &options);
+            for sample in &mut tile.samples {
+                *sample *= opacity;
+            }
+            composite(
+                framebuffer,
+                &tile.samples,
+                tile.width,
+                tile.height,
+                0,
+                0,
+                tile.width,
+                position.x + offset_x,
+                position.y + offset_y,
+                luminance,
+            );
+        }
+    }
+}
+
+/// Palette index of `COLR` layers drawn with the text foreground color.
+const FOREGROUND_INDEX: u16 = 0xFFFF;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Layer glyphs and colors of a `COLR` version 0 base glyph.
+///
+/// Reads the layer records directly to tell foreground layers apart from palette entries
+/// of any color, `None` denotes the foreground color.
+pub(crate) fn colr_layers(colr: &[u8], cpal: &ttf_parser::cpal::Table, id: GlyphId, palette: u16) -> Option<Vec<(GlyphId, Option<[u8; 4]>)>> {
+    let num_base_glyphs = read_u16(colr, 2)? as usize;
+    let base_glyphs = read_u32(colr, 4)? as usize;
+    let layers = read_u32(colr, 8)? as usize;
+    let num_layers = read_u16(colr, 12)? as usize;
+
+    let (first, count) = (0..num_base_glyphs).find_map(|i| {
+        let record = base_glyphs + 6 * i;
+        if read_u16(colr, record)? == id {
+            Some((read_u16(colr, record + 2)? as usize, read_u16(colr, record + 4)? as usize))
+        } else {
+            None
+        }
+    })?;
+    if first + count > num_layers {
+        return None;
+    }
+
+    (first..first + count)
+        .map(|i| {
+            let record = layers + 4 * i;
+            let glyph = read_u16(colr, record)?;
+            let color = match read_u16(colr, record + 2)? {
+                FOREGROUND_INDEX => None,
+                index => {
+                    let color = cpal.get(palette, index)?;
+                    Some([color.red, color.green, color.blue, color.alpha])
+                }
+            };
+            Some((glyph, color))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layer_luminance() {
+        let font = crate::Font::from_bytes(include_bytes!("../../assets/Roboto-Regular.ttf")).unwrap();
+        let glyph = font.glyph(font.glyph_id('o').unwrap()).unwrap();
+
+        let layer = ColorLayer {
+            glyph,
+            color: Some([255, 255, 255, 128]),
+        };
+        let (luminance, opacity) = layer.luminance(0.0);
+        assert!((luminance - 1.0).abs() < 1e-6);
+        assert!((opacity - 128.0 / 255.0).abs() < 1e-6);
+
+        let layer = ColorLayer { color: None, ..layer };
+        assert_eq!(layer.luminance(0.25), (0.25, 1.0));
+
+        // Plain outline fonts have no color glyphs.
+        assert!(font.color_glyph(font.glyph_id('o').unwrap(), 0).is_none());
+    }
+
+    #[test]
+    fn palette_layers() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        // Layers: opaque red square, foreground square, fully transparent black square.
+        let font = crate::Font::from_bytes(include_bytes!("../../assets/Fixture-VarColr.ttf")).unwrap();
+        let id = font.glyph_id('B').unwrap();
+        assert!(font.is_color_glyph(id));
+        let glyph = font.color_glyph(id, 0).unwrap();
+        assert_eq!(glyph.layers.iter().map(|layer| layer.glyph.id).collect::<Vec<_>>(), [3, 4, 5]);
+        assert_eq!(glyph.layers.iter().map(|layer| layer.color).collect::<Vec<_>>(), [Some([255, 0, 0, 255]), None, Some([0, 0, 0, 0])]);
+        assert!(font.color_glyph(font.glyph_id('A').unwrap(), 0).is_none());
+
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(32, 32);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+        // 30 pixels per em, layers cover 0..9, 6..15 and 9..18 pixels above the baseline.
+        glyph.draw(&mut rasterizer, &mut framebuffer, 30.0, GlyphPosition::quantize(glam::vec2(0.0, 30.0), 1), 1.0);
+
+        let sample = |x: u32, y: u32| framebuffer.samples[(y * 32 + x) as usize];
+        let red = 0.2126;
+        assert!((sample(2, 27) - red).abs() < 1e-4);
+        // Foreground layer on top of the red one.
+        assert!((sample(7, 23) - 1.0).abs() < 1e-4);
+        // Transparent layer stays invisible.
+        assert!((sample(12, 18) - 1.0).abs() < 1e-4);
+        assert_eq!(sample(16, 14), 0.0);
+    }
+}
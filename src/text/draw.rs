@@ -0,0 +1,291 @@
+//! Drawing glyphs and text runs with subpixel positioning.
+
+use crate::{composite, rasterize_tile, Curve, Font, Framebuffer, Glyph, PositionedGlyph, Rasterizer, TextRenderOptions};
+
+/// Glyph origin snapped to whole pixels vertically and to `steps` phases horizontally.
+///
+/// Quantizing the origin keeps the rendered shape of a glyph identical wherever it's placed,
+/// independent of the rasterizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphPosition {
+    pub x: i32,
+    pub y: i32,
+    /// Horizontal subpixel phase in `0..steps`.
+    pub phase: u32,
+    pub steps: u32,
+}
+
+impl GlyphPosition {
+    pub fn quantize(origin: glam::Vec2, steps: u32) -> Self {
+        let steps = steps.max(1);
+        let q = (origin.x() * steps as f32).round() as i64;
+        GlyphPosition {
+            x: q.div_euclid(steps as i64) as i32,
+            y: origin.y().round() as i32,
+            phase: q.rem_euclid(steps as i64) as u32,
+            steps,
+        }
+    }
+
+    /// Fractional horizontal offset of the phase.
+    pub fn fraction(&self) -> f32 {
+        self.phase as f32 / self.steps as f32
+    }
+
+    /// Snapped origin in pixels.
+    pub fn origin(&self) -> glam::Vec2 {
+        glam::vec2(self.x as f32 + self.fraction(), self.y as f32)
+    }
+}
+
+/// Draw a single glyph with baseline origin at `position`, `size` in pixels per em.
+///
+/// The glyph is rasterized into a separate tile and its coverage blended towards `value` on top
+/// of the existing samples, overlapping glyphs and backgrounds are preserved.
+pub fn draw_glyph(
+    rasterizer: &mut dyn Rasterizer,
+    framebuffer: &mut Framebuffer,
+    glyph: &Glyph,
+    size: f32,
+    position: GlyphPosition,
+    options: &TextRenderOptions,
+    value: f32,
+) {
+    let darkened = options.darken(glyph, size);
+    let outline = darkened.as_ref().unwrap_or(glyph);
+    if outline.is_empty() {
+        return;
+    }
+
+    let (tile, offset_x, offset_y) = rasterize_tile(rasterizer, framebuffer, outline, size, position, options);
+    composite(
+        framebuffer,
+        &tile.samples,
+        tile.width,
+        tile.height,
+        0,
+        0,
+        tile.width,
+        position.x + offset_x,
+        position.y + offset_y,
+        value,
+    );
+}
+
+/// Draw an already darkened glyph outline into a scratch framebuffer.
+pub(crate) fn draw_outline(
+    rasterizer: &mut dyn Rasterizer,
+    framebuffer: &mut Framebuffer,
+    outline: &Glyph,
+    size: f32,
+    position: GlyphPosition,
+) {
+    if outline.is_empty() {
+        return;
+    }
+
+    let segments = outline
+        .segments
+        .iter()
+        .map(|segment| Curve::monotize_path(segment))
+        .collect::<Vec<_>>();
+    let path = rasterizer.create_path(&segments);
+    let rect = outline.rect(size, position.origin());
+    rasterizer.cmd_draw(framebuffer, rect, &path);
+}
+
+/// Draw a single line of text starting at the baseline `origin`.
+///
+/// The pen advances in unquantized pixels, only the glyph origins are snapped to
+/// `subpixel_steps` horizontal phases. Returns the pen position after the last glyph.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text(
+    rasterizer: &mut dyn Rasterizer,
+    framebuffer: &mut Framebuffer,
+    font: &Font,
+    text: &str,
+    size: f32,
+    origin: glam::Vec2,
+    subpixel_steps: u32,
+    options: &TextRenderOptions,
+    value: f32,
+) -> glam::Vec2 {
+    let scale = size / font.units_per_em();
+    let mut pen = origin;
+    for c in text.chars() {
+        let glyph = match font.glyph_id(c).and_then(|id| font.glyph(id)) {
+            Some(glyph) => glyph,
+            None => continue,
+        };
+        let position = GlyphPosition::quantize(pen, subpixel_steps);
+        draw_glyph(rasterizer, framebuffer, &glyph, size, position, options, value);
+        pen += glam::vec2(glyph.advance * scale, 0.0);
+    }
+    pen
+}
+
+/// Draw glyphs positioned by a `Layout` at `size` pixels per em.
+///
+/// Each glyph is composited on top of the previous ones, kerned pairs and tight line
+/// heights with overlapping glyph boxes keep the coverage of all glyphs.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_glyphs(
+    rasterizer: &mut dyn Rasterizer,
+    framebuffer: &mut Framebuffer,
+    font: &Font,
+    glyphs: &[PositionedGlyph],
+    size: f32,
+    subpixel_steps: u32,
+    options: &TextRenderOptions,
+    value: f32,
+) {
+    for positioned in glyphs {
+        if let Some(glyph) = font.glyph(positioned.id) {
+            let position = GlyphPosition::quantize(positioned.position, subpixel_steps);
+            draw_glyph(rasterizer, framebuffer, &glyph, size, position, options, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quantize() {
+        let pos = GlyphPosition::quantize(glam::vec2(10.3, 4.6), 4);
+        assert_eq!((pos.x, pos.y, pos.phase), (10, 5, 1));
+        assert_eq!(pos.origin(), glam::vec2(10.25, 5.0));
+
+        // Rounding up into the next pixel wraps the phase.
+        let pos = GlyphPosition::quantize(glam::vec2(10.9, 0.0), 4);
+        assert_eq!((pos.x, pos.phase), (11, 0));
+
+        let pos = GlyphPosition::quantize(glam::vec2(-0.25, 0.0), 4);
+        assert_eq!((pos.x, pos.phase), (-1, 3));
+    }
+
+    #[test]
+    fn consistent_shape() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        let font = Font::from_bytes(include_bytes!("../../assets/Roboto-Regular.ttf")).unwrap();
+        let glyph = font.glyph(font.glyph_id('l').unwrap()).unwrap();
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut framebuffer = Framebuffer::new(32, 16);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut framebuffer);
+
+        // Origins with the same phase produce the same coverage, shifted by whole pixels.
+        draw_glyph(&mut rasterizer, &mut framebuffer, &glyph, 12.0, GlyphPosition::quantize(glam::vec2(2.3, 12.0), 4), &TextRenderOptions::default(), 1.0);
+        draw_glyph(&mut rasterizer, &mut framebuffer, &glyph, 12.0, GlyphPosition::quantize(glam::vec2(18.2, 12.0), 4), &TextRenderOptions::default(), 1.0);
+        for y in 0..16 {
+            let row = &framebuffer.samples[(y * 32) as usize..(y * 32 + 32) as usize];
+            assert_eq!(row[..16], row[16..]);
+        }
+    }
+
+    #[test]
+    fn overlapping_glyphs() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Sampler, UniformSampler};
+
+        let font = Font::from_bytes(include_bytes!("../../assets/Roboto-Regular.ttf")).unwrap();
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut background = Framebuffer::new(48, 24);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut background);
+        rasterizer.cmd_fill(&mut background, glam::vec2(0.0, 0.0), glam::vec2(48.0, 24.0), 0.5);
+
+        let options = TextRenderOptions::default();
+        let mut single = background.clone();
+        draw_text(&mut rasterizer, &mut single, &font, "A", 20.0, glam::vec2(4.0, 20.0), 4, &options, 1.0);
+        let mut pair = background.clone();
+        draw_text(&mut rasterizer, &mut pair, &font, "AV", 20.0, glam::vec2(4.0, 20.0), 4, &options, 1.0);
+
+        // Samples only ever move towards the text color, the box around 'V' doesn't erase 'A' or the background.
+        assert!(single.samples.iter().zip(&background.samples).any(|(s, b)| s > b));
+        assert!(single.samples.iter().zip(&pair.samples).all(|(s, p)| *p >= *s));
+        assert!(pair.samples.iter().all(|&s| s >= 0.5));
+
+        // Coverage adjustment only applies to the glyph, not to the samples below.
+        let contrast = TextRenderOptions {
+            gamma: 1.8,
+            contrast: 0.5,
+            ..options
+        };
+        let mut adjusted = single.clone();
+        draw_text(&mut rasterizer, &mut adjusted, &font, "V", 20.0, glam::vec2(40.0, 20.0), 4, &contrast, 1.0);
+        for y in 0..24 {
+            let row = y * 48;
+            assert_eq!(adjusted.samples[row..row + 36], single.samples[row..row + 36]);
+        }
+
+        let mut adjusted = background.clone();
+        draw_text(&mut rasterizer, &mut adjusted, &font, "V", 20.0, glam::vec2(40.0, 20.0), 4, &contrast, 1.0);
+        let mut plain = background.clone();
+        draw_text(&mut rasterizer, &mut plain, &font, "V", 20.0, glam::vec2(40.0, 20.0), 4, &options, 1.0);
+        for (a, p) in adjusted.samples.iter().zip(&plain.samples) {
+            // Blending towards 1 over 0.5 halves the glyph coverage.
+            let coverage = 2.0 * (p - 0.5);
+            assert!((a - (0.5 + 0.5 * contrast.adjust_coverage(coverage))).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn text_value() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, GlyphCache, Sampler, UniformSampler};
+
+        let font = Font::from_bytes(include_bytes!("../../assets/Roboto-Regular.ttf")).unwrap();
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut background = Framebuffer::new(32, 24);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut background);
+        rasterizer.cmd_fill(&mut background, glam::vec2(0.0, 0.0), glam::vec2(32.0, 24.0), 1.0);
+
+        // Dark text on a bright background, uncached and cached drawing agree.
+        let options = TextRenderOptions::default();
+        let mut uncached = background.clone();
+        draw_text(&mut rasterizer, &mut uncached, &font, "A", 20.0, glam::vec2(4.3, 20.0), 4, &options, 0.25);
+        let mut cached = background.clone();
+        let mut cache = GlyphCache::new(64, 64, 4);
+        let glyph = font.glyph_id('A').unwrap();
+        cache.draw(&mut rasterizer, &mut cached, 0, &font, glyph, 20.0, glam::vec2(4.3, 20.0), 0.25);
+
+        assert_eq!(uncached.samples, cached.samples);
+        assert!(uncached.samples.contains(&0.25));
+        assert!(uncached.samples.iter().all(|&s| (0.25..=1.0).contains(&s)));
+    }
+
+    #[test]
+    fn overlapping_layout() {
+        use crate::{BoxFilter, CoarseDirection, CoarseRasterizer, Layout, Sampler, UniformSampler};
+
+        let font = Font::from_bytes(include_bytes!("../../assets/Roboto-Regular.ttf")).unwrap();
+        let mut rasterizer = CoarseRasterizer {
+            direction: CoarseDirection::Y,
+            filter: BoxFilter::new(-0.5, 0.5),
+        };
+        let mut empty = Framebuffer::new(64, 48);
+        UniformSampler { nx: 1, ny: 1 }.populate(&mut empty);
+
+        // Kerned pairs wrapped onto lines closer than the glyph height.
+        let layout = Layout::new(20.0).with_max_width(40.0).with_line_height(0.6);
+        let glyphs = layout.layout(&font, "AVA VAV", glam::vec2(2.0, 16.0));
+        assert!(glyphs.iter().any(|glyph| glyph.position.y() > 16.0));
+        let options = TextRenderOptions::default();
+        let mut framebuffer = empty.clone();
+        draw_glyphs(&mut rasterizer, &mut framebuffer, &font, &glyphs, 20.0, 4, &options, 1.0);
+
+        for glyph in &glyphs {
+            let mut single = empty.clone();
+            draw_glyphs(&mut rasterizer, &mut single, &font, std::slice::from_ref(glyph), 20.0, 4, &options, 1.0);
+            assert!(single.samples.iter().zip(&framebuffer.samples).all(|(s, f)| f >= s));
+        }
+    }
+}
@@ -0,0 +1,515 @@
+//! TrueType/OpenType font loading and glyph outline extraction.
+
+use crate::{Aabb, ColorGlyph, ColorLayer, Curve, Join, PathBuilder, Rect, Segment};
+use crate::text::color::colr_layers;
+use std::fmt;
+
+pub type GlyphId = u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontError {
+    Parse(ttf_parser::FaceParsingError),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Parse(err) => write!(f, "failed to parse font: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<ttf_parser::FaceParsingError> for FontError {
+    fn from(err: ttf_parser::FaceParsingError) -> Self {
+        FontError::Parse(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariationAxis {
+    pub tag: [u8; 4],
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+pub struct Font<'a> {
+    face: ttf_parser::Face<'a>,
+}
+
+/// Glyph outline in curve space: font units with y pointing down, origin at the baseline.
+pub struct Glyph {
+    pub id: GlyphId,
+    /// Closed contours, oriented as expected by the rasterizers.
+    pub segments: Vec<Segment>,
+    pub bounds: Aabb,
+    /// Horizontal advance in font units.
+    pub advance: f32,
+    pub units_per_em: f32,
+}
+
+impl Glyph {
+    /// Mapping of the outline onto the framebuffer.
+    ///
+    /// `size` is given in pixels per em, `origin` is the baseline origin in pixels.
+    pub fn rect(&self, size: f32, origin: glam::Vec2) -> Rect {
+        let scale = size / self.units_per_em;
+        Rect {
+            offset_local: origin + self.bounds.min * scale,
+            extent_local: (self.bounds.max - self.bounds.min) * scale,
+            offset_curve: self.bounds.min,
+            extent_curve: self.bounds.max - self.bounds.min,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Grow the outline by `amount` (font units) per side, counters shrink accordingly.
+    ///
+    /// Control points are moved along the miter of their adjacent edges, similar to `FT_Outline_Embolden`.
+    pub fn embolden(&self, amount: glam::Vec2) -> Glyph {
+        let margin = amount * EMBOLDEN_MITER_LIMIT;
+        Glyph {
+            id: self.id,
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| embolden_contour(segment, amount))
+                .collect(),
+            bounds: Aabb {
+                min: self.bounds.min - margin,
+                max: self.bounds.max + margin,
+            },
+            advance: self.advance,
+            units_per_em: self.units_per_em,
+        }
+    }
+
+    /// Exact outline grown (or shrunk for negative values) by `distance` font units, see `Curve::offset_segments`.
+    ///
+    /// Slower than `embolden` but free of overlaps, suited for halos and outlined text.
+    pub fn offset(&self, distance: f32, join: Join, tolerance: f32) -> Glyph {
+        let segments = Curve::offset_segments(&self.segments, distance, join, tolerance);
+        Glyph {
+            id: self.id,
+            bounds: Aabb::from_segments(&segments),
+            segments,
+            advance: self.advance,
+            units_per_em: self.units_per_em,
+        }
+    }
+}
+
+/// Maximum displacement of a control point relative to the emboldening amount.
+const EMBOLDEN_MITER_LIMIT: f32 = 2.0;
+
+/// Offset the control polygon of a closed contour.
+///
+/// Expects the orientation produced by `Curve::orient_segments`.
+fn embolden_contour(segment: &Segment, amount: glam::Vec2) -> Segment {
+    let mut points = Vec::new();
+    let mut starts = Vec::new();
+    for curve in segment {
+        starts.push(points.len());
+        match *curve {
+            Curve::Line { p0, .. } => points.push(p0),
+            Curve::Quad { p0, p1, .. } => {
+                points.push(p0);
+                points.push(p1);
+            }
+        }
+    }
+
+    let n = points.len();
+    let distinct = |from: usize, step: usize| {
+        (1..n)
+            .map(|k| points[(from + k * step) % n])
+            .find(|p| *p != points[from])
+    };
+
+    let moved = (0..n)
+        .map(|i| {
+            let p = points[i];
+            let (prev, next) = match (distinct(i, n - 1), distinct(i, 1)) {
+                (Some(prev), Some(next)) => (prev, next),
+                _ => return p,
+            };
+
+            let e0 = (p - prev).normalize();
+            let e1 = (next - p).normalize();
+            let n0 = glam::vec2(-e0.y(), e0.x());
+            let n1 = glam::vec2(-e1.y(), e1.x());
+
+            let cos = n0.dot(n1);
+            let miter = if 1.0 + cos > 1.0 / (EMBOLDEN_MITER_LIMIT * EMBOLDEN_MITER_LIMIT) {
+                (n0 + n1) / (1.0 + cos)
+            } else {
+                // Sharp spike, clamp the miter length.
+                (n0 + n1).normalize() * EMBOLDEN_MITER_LIMIT
+            };
+            let miter = if miter.x().is_finite() && miter.y().is_finite() { miter } else { n0 };
+
+            p + miter * amount
+        })
+        .collect::<Vec<_>>();
+
+    segment
+        .iter()
+        .enumerate()
+        .map(|(k, curve)| {
+            let start = starts[k];
+            let end = moved[starts.get(k + 1).copied().unwrap_or(0)];
+            match curve {
+                Curve::Line { .. } => Curve::Line { p0: moved[start], p1: end },
+                Curve::Quad { .. } => Curve::Quad {
+                    p0: moved[start],
+                    p1: moved[start + 1],
+                    p2: end,
+                },
+            }
+        })
+        .collect()
+}
+
+impl<'a> Font<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, FontError> {
+        Self::from_collection(data, 0)
+    }
+
+    /// Font at `index` of a font collection (`.ttc`).
+    pub fn from_collection(data: &'a [u8], index: u32) -> Result<Self, FontError> {
+        Ok(Font {
+            face: ttf_parser::Face::parse(data, index)?,
+        })
+    }
+
+    pub fn is_variable(&self) -> bool {
+        self.face.is_variable()
+    }
+
+    pub fn variation_axes(&self) -> Vec<VariationAxis> {
+        self.face
+            .variation_axes()
+            .into_iter()
+            .map(|axis| VariationAxis {
+                tag: axis.tag.to_bytes(),
+                min: axis.min_value,
+                default: axis.def_value,
+                max: axis.max_value,
+            })
+            .collect()
+    }
+
+    /// Set the user space coordinate of a variation axis (e.g. `b"wght"`).
+    ///
+    /// Outlines, bounds and advances reflect the variation. Returns `false` if the font has no such axis.
+    pub fn set_variation(&mut self, tag: &[u8; 4], value: f32) -> bool {
+        self.face.set_variation(ttf_parser::Tag::from_bytes(tag), value).is_some()
+    }
+
+    pub fn with_variation(mut self, tag: &[u8; 4], value: f32) -> Self {
+        self.set_variation(tag, value);
+        self
+    }
+
+    pub fn is_color_glyph(&self, id: GlyphId) -> bool {
+        self.face.is_color_glyph(ttf_parser::GlyphId(id))
+    }
+
+    /// Layers of a `COLR` version 0 glyph using the colors of `palette`.
+    ///
+    /// Returns `None` for glyphs without version 0 layers, version 1 paints are not supported.
+    pub fn color_glyph(&self, id: GlyphId, palette: u16) -> Option<ColorGlyph> {
+        let raw = self.face.raw_face();
+        let colr = raw.table(ttf_parser::Tag::from_bytes(b"COLR"))?;
+        let cpal = ttf_parser::cpal::Table::parse(raw.table(ttf_parser::Tag::from_bytes(b"CPAL"))?)?;
+
+        let layers = colr_layers(colr, &cpal, id, palette)?
+            .into_iter()
+            .filter_map(|(glyph, color)| Some(ColorLayer { glyph: self.glyph(glyph)?, color }))
+            .collect();
+
+        Some(ColorGlyph { id, layers })
+    }
+
+    pub fn units_per_em(&self) -> f32 {
+        self.face.units_per_em() as f32
+    }
+
+    /// Ascender in font units (y up).
+    pub fn ascender(&self) -> f32 {
+        self.face.ascender() as f32
+    }
+
+    /// Descender in font units (y up), usually negative.
+    pub fn descender(&self) -> f32 {
+        self.face.descender() as f32
+    }
+
+    pub fn line_gap(&self) -> f32 {
+        self.face.line_gap() as f32
+    }
+
+    pub fn glyph_id(&self, c: char) -> Option<GlyphId> {
+        self.face.glyph_index(c).map(|id| id.0)
+    }
+
+    /// Horizontal advance in font units.
+    pub fn advance(&self, id: GlyphId) -> f32 {
+        self.face
+            .glyph_hor_advance(ttf_parser::GlyphId(id))
+            .unwrap_or(0) as f32
+    }
+
+    /// Horizontal kerning adjustment between two glyphs in font units.
+    ///
+    /// Uses the legacy `kern` table if present, otherwise the pair adjustments of the
+    /// first `kern` feature in `GPOS`.
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> f32 {
+        let (left, right) = (ttf_parser::GlyphId(left), ttf_parser::GlyphId(right));
+
+        if let Some(kern) = self.face.tables().kern {
+            return kern
+                .subtables
+                .into_iter()
+                .filter(|subtable| subtable.horizontal && !subtable.variable && !subtable.has_cross_stream)
+                .filter_map(|subtable| subtable.glyphs_kerning(left, right))
+                .map(|value| value as f32)
+                .sum();
+        }
+
+        self.gpos_kerning(left, right).unwrap_or(0.0)
+    }
+
+    fn gpos_kerning(&self, left: ttf_parser::GlyphId, right: ttf_parser::GlyphId) -> Option<f32> {
+        use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+
+        let gpos = self.face.tables().gpos?;
+        let feature = gpos.features.find(ttf_parser::Tag::from_bytes(b"kern"))?;
+
+        let mut kerning = 0.0;
+        for lookup in feature.lookup_indices.into_iter().filter_map(|index| gpos.lookups.get(index)) {
+            // The first subtable covering the pair applies.
+            let value = lookup
+                .subtables
+                .into_iter::<PositioningSubtable>()
+                .filter_map(|subtable| match subtable {
+                    PositioningSubtable::Pair(pair) => Some(pair),
+                    _ => None,
+                })
+                .find_map(|pair| {
+                    let index = pair.coverage().get(left)?;
+                    let (first, _) = match pair {
+                        PairAdjustment::Format1 { sets, .. } => sets.get(index)?.get(right)?,
+                        PairAdjustment::Format2 { classes, matrix, .. } => {
+                            matrix.get((classes.0.get(left), classes.1.get(right)))?
+                        }
+                    };
+                    Some(first.x_advance)
+                });
+            kerning += value.unwrap_or(0) as f32;
+        }
+
+        Some(kerning)
+    }
+
+    /// Outline of a glyph, `None` for glyph ids not contained in the font.
+    ///
+    /// Glyphs without outline (e.g. space) return an empty glyph.
+    pub fn glyph(&self, id: GlyphId) -> Option<Glyph> {
+        if id >= self.face.number_of_glyphs() {
+            return None;
+        }
+
+        let mut outline = OutlineBuilder::new();
+        let bounds = match self.face.outline_glyph(ttf_parser::GlyphId(id), &mut outline) {
+            Some(bbox) => Aabb {
+                min: glam::vec2(bbox.x_min as f32, -bbox.y_max as f32),
+                max: glam::vec2(bbox.x_max as f32, -bbox.y_min as f32),
+            },
+            None => Aabb::zero(),
+        };
+
+        Some(Glyph {
+            id,
+            segments: Curve::orient_segments(&outline.finish()),
+            bounds,
+            advance: self.advance(id),
+            units_per_em: self.units_per_em(),
+        })
+    }
+}
+
+/// Collects the contours of an outline, flipping y to point downwards.
+struct OutlineBuilder {
+    segments: Vec<Segment>,
+    path: Option<PathBuilder>,
+    first: glam::Vec2,
+    last: glam::Vec2,
+}
+
+impl OutlineBuilder {
+    fn new() -> Self {
+        OutlineBuilder {
+            segments: Vec::new(),
+            path: None,
+            first: glam::vec2(0.0, 0.0),
+            last: glam::vec2(0.0, 0.0),
+        }
+    }
+
+    fn finish(mut self) -> Vec<Segment> {
+        ttf_parser::OutlineBuilder::close(&mut self);
+        self.segments
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        ttf_parser::OutlineBuilder::close(self);
+        let p = glam::vec2(x, -y);
+        self.path = Some(PathBuilder::new().move_to(p));
+        self.first = p;
+        self.last = p;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = glam::vec2(x, -y);
+        self.path = self.path.take().map(|path| path.line_to(p));
+        self.last = p;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p = glam::vec2(x, -y);
+        self.path = self.path.take().map(|path| path.quad_to(glam::vec2(x1, -y1), p));
+        self.last = p;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p = glam::vec2(x, -y);
+        self.path = self
+            .path
+            .take()
+            .map(|path| path.cubic_to(glam::vec2(x1, -y1), glam::vec2(x2, -y2), p));
+        self.last = p;
+    }
+
+    fn close(&mut self) {
+        if let Some(mut path) = self.path.take() {
+            // Avoid zero length closing lines, the outline may already end at the start point.
+            if self.last != self.first {
+                path = path.line_to(self.first);
+            }
+            let segment = path.finish();
+            if !segment.is_empty() {
+                self.segments.push(segment);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROBOTO: &[u8] = include_bytes!("../../assets/Roboto-Regular.ttf");
+
+    #[test]
+    fn closed_contours() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let glyph = font.glyph(font.glyph_id('o').unwrap()).unwrap();
+
+        // outer contour and counter
+        assert_eq!(glyph.segments.len(), 2);
+        for segment in &glyph.segments {
+            for pair in segment.windows(2) {
+                assert_eq!(pair[0].end(), pair[1].start());
+            }
+            assert_eq!(segment.last().unwrap().end(), segment[0].start());
+        }
+
+        let area: f32 = glyph.segments.iter().flatten().map(Curve::signed_area).sum();
+        assert!(area < 0.0);
+    }
+
+    #[test]
+    fn embolden() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let glyph = font.glyph(font.glyph_id('o').unwrap()).unwrap();
+        let bold = glyph.embolden(glam::vec2(20.0, 20.0));
+
+        let area = |glyph: &Glyph| -> f32 {
+            glyph.segments.iter().flatten().map(Curve::signed_area).sum()
+        };
+        assert!(area(&bold) < area(&glyph));
+
+        // Outer contour grows, counter shrinks.
+        let width = |segment: &Segment| {
+            Aabb::from_curves(segment).extent().x()
+        };
+        assert!(width(&glyph.segments[0]) + 39.0 < width(&bold.segments[0]));
+        assert!(width(&glyph.segments[1]) - 39.0 > width(&bold.segments[1]));
+
+        let halo = glyph.offset(20.0, Join::Round, 1.0);
+        assert_eq!(halo.segments.len(), 2);
+        assert!((halo.bounds.extent().x() - glyph.bounds.extent().x() - 40.0).abs() < 1.0);
+        assert!(area(&halo) < area(&glyph));
+    }
+
+    #[test]
+    fn kerning() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let pair = |a, b| font.kerning(font.glyph_id(a).unwrap(), font.glyph_id(b).unwrap());
+        assert!(pair('A', 'V') < 0.0);
+        assert!(pair('T', 'o') < 0.0);
+        assert_eq!(pair('o', 'o'), 0.0);
+    }
+
+    #[test]
+    fn variations() {
+        let mut font = Font::from_bytes(ROBOTO).unwrap();
+        assert!(!font.is_variable());
+        assert!(font.variation_axes().is_empty());
+        assert!(!font.set_variation(b"wght", 700.0));
+
+        // Weight moves the right edge and the advance of 'A' by up to 200 units.
+        let mut font = Font::from_bytes(include_bytes!("../../assets/Fixture-VarColr.ttf")).unwrap();
+        assert!(font.is_variable());
+        assert_eq!(
+            font.variation_axes(),
+            [VariationAxis { tag: *b"wght", min: 100.0, default: 400.0, max: 900.0 }]
+        );
+        let id = font.glyph_id('A').unwrap();
+        let extent = |font: &Font| {
+            let glyph = font.glyph(id).unwrap();
+            let area: f32 = glyph.segments.iter().flatten().map(Curve::signed_area).sum();
+            (glyph.bounds.max.x(), glyph.advance, area.abs())
+        };
+        assert_eq!(extent(&font), (500.0, 600.0, 400.0 * 400.0));
+
+        assert!(font.set_variation(b"wght", 900.0));
+        assert_eq!(extent(&font), (700.0, 800.0, 600.0 * 400.0));
+
+        let font = font.with_variation(b"wght", 650.0);
+        assert_eq!(extent(&font), (600.0, 700.0, 500.0 * 400.0));
+    }
+
+    #[test]
+    fn rect_mapping() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let glyph = font.glyph(font.glyph_id('H').unwrap()).unwrap();
+
+        // Glyph sits on the baseline, above the origin.
+        let rect = glyph.rect(font.units_per_em(), glam::vec2(10.0, 100.0));
+        assert_eq!(rect.offset_local.y() + rect.extent_local.y(), 100.0);
+        assert_eq!(rect.extent_local, rect.extent_curve);
+
+        let space = font.glyph(font.glyph_id(' ').unwrap()).unwrap();
+        assert!(space.is_empty());
+        assert!(space.advance > 0.0);
+    }
+}
@@ -0,0 +1,257 @@
+//! Line breaking and positioning of glyphs.
+
+use crate::{Font, GlyphId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Glyph placed by a `Layout`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    pub id: GlyphId,
+    /// Baseline origin in pixels.
+    pub position: glam::Vec2,
+    /// Byte offset of the source character.
+    pub index: usize,
+    pub line: usize,
+}
+
+/// Simple horizontal text layout: one glyph per character, greedy line breaking at whitespace.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    /// Font size in pixels per em.
+    pub size: f32,
+    /// Wrap lines exceeding the width (in pixels).
+    pub max_width: Option<f32>,
+    pub alignment: Alignment,
+    /// Line spacing relative to the font's line height.
+    pub line_height: f32,
+    pub kerning: bool,
+}
+
+struct Item {
+    id: GlyphId,
+    x: f32,
+    advance: f32,
+    index: usize,
+    whitespace: bool,
+}
+
+struct Line {
+    items: Vec<Item>,
+}
+
+impl Line {
+    /// Width without trailing whitespace.
+    fn width(&self) -> f32 {
+        self.items
+            .iter()
+            .rev()
+            .find(|item| !item.whitespace)
+            .map_or(0.0, |item| item.x + item.advance)
+    }
+}
+
+impl Layout {
+    pub fn new(size: f32) -> Self {
+        Layout {
+            size,
+            max_width: None,
+            alignment: Alignment::Left,
+            line_height: 1.0,
+            kerning: true,
+        }
+    }
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn with_kerning(mut self, kerning: bool) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Distance between two baselines in pixels.
+    pub fn line_advance(&self, font: &Font) -> f32 {
+        let scale = self.size / font.units_per_em();
+        (font.ascender() - font.descender() + font.line_gap()) * scale * self.line_height
+    }
+
+    /// Position the glyphs of `text` inside a box with top left corner at `origin`.
+    ///
+    /// Characters missing in the font are skipped, `\n` starts a new line.
+    pub fn layout(&self, font: &Font, text: &str, origin: glam::Vec2) -> Vec<PositionedGlyph> {
+        let scale = self.size / font.units_per_em();
+
+        let mut lines = Vec::new();
+        for (start, paragraph) in paragraphs(text) {
+            let mut line = Line { items: Vec::new() };
+            let mut pen = 0.0;
+            let mut prev = None;
+
+            for (i, c) in paragraph.char_indices() {
+                let id = match font.glyph_id(c) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                let whitespace = c.is_whitespace();
+                let advance = font.advance(id) * scale;
+                let kerning = match prev {
+                    Some(prev) if self.kerning => font.kerning(prev, id) * scale,
+                    _ => 0.0,
+                };
+                let mut x = pen + kerning;
+
+                let overflow = matches!(self.max_width, Some(max_width) if x + advance > max_width);
+                if overflow && !whitespace && line.items.iter().any(|item| !item.whitespace) {
+                    // Break after the last whitespace run, or before the current glyph if the word
+                    // doesn't fit on a line at all.
+                    let split = line
+                        .items
+                        .iter()
+                        .rposition(|item| item.whitespace)
+                        .filter(|&ws| line.items[..ws].iter().any(|item| !item.whitespace))
+                        .map_or(line.items.len(), |ws| ws + 1);
+
+                    let mut tail = line.items.split_off(split);
+                    let shift = tail.first().map_or(x, |item| item.x);
+                    for item in &mut tail {
+                        item.x -= shift;
+                    }
+                    x -= shift;
+
+                    lines.push(line);
+                    line = Line { items: tail };
+                }
+
+                line.items.push(Item {
+                    id,
+                    x,
+                    advance,
+                    index: start + i,
+                    whitespace,
+                });
+                pen = x + advance;
+                prev = Some(id);
+            }
+
+            lines.push(line);
+        }
+
+        let box_width = self
+            .max_width
+            .unwrap_or_else(|| lines.iter().map(Line::width).fold(0.0, f32::max));
+        let ascender = font.ascender() * scale;
+        let line_advance = self.line_advance(font);
+
+        lines
+            .iter()
+            .enumerate()
+            .flat_map(|(n, line)| {
+                let offset = match self.alignment {
+                    Alignment::Left => 0.0,
+                    Alignment::Center => 0.5 * (box_width - line.width()),
+                    Alignment::Right => box_width - line.width(),
+                };
+                let baseline = origin.y() + ascender + n as f32 * line_advance;
+
+                line.items.iter().map(move |item| PositionedGlyph {
+                    id: item.id,
+                    position: glam::vec2(origin.x() + offset + item.x, baseline),
+                    index: item.index,
+                    line: n,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Split into lines at `\n`, including the byte offset of each line.
+fn paragraphs(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split('\n').scan(0, |start, line| {
+        let offset = *start;
+        *start += line.len() + 1;
+        Some((offset, line.strip_suffix('\r').unwrap_or(line)))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROBOTO: &[u8] = include_bytes!("../../assets/Roboto-Regular.ttf");
+
+    fn lines(glyphs: &[PositionedGlyph], text: &str) -> Vec<String> {
+        let mut lines = Vec::<String>::new();
+        for glyph in glyphs {
+            if glyph.line == lines.len() {
+                lines.push(String::new());
+            }
+            lines[glyph.line].push(text[glyph.index..].chars().next().unwrap());
+        }
+        lines.iter().map(|line| line.trim_end().to_string()).collect()
+    }
+
+    #[test]
+    fn wrapping() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let text = "the quick brown fox\njumps";
+        let layout = Layout::new(20.0);
+
+        let glyphs = layout.layout(&font, text, glam::vec2(0.0, 0.0));
+        assert_eq!(lines(&glyphs, text), ["the quick brown fox", "jumps"]);
+
+        let start = glyphs.iter().find(|glyph| glyph.line == 1).unwrap().position.x();
+        assert_eq!(start, 0.0);
+
+        let glyphs = layout.with_max_width(100.0).layout(&font, text, glam::vec2(0.0, 0.0));
+        assert_eq!(lines(&glyphs, text), ["the quick", "brown fox", "jumps"]);
+
+        // Words longer than a line are broken anywhere.
+        let glyphs = layout.with_max_width(30.0).layout(&font, "abcdefgh", glam::vec2(0.0, 0.0));
+        assert!(glyphs.last().unwrap().line > 0);
+        assert_eq!(glyphs.iter().filter(|glyph| glyph.position.x() == 0.0).count(), glyphs.last().unwrap().line + 1);
+    }
+
+    #[test]
+    fn alignment() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let layout = Layout::new(20.0).with_max_width(200.0);
+
+        let left = layout.layout(&font, "fox", glam::vec2(10.0, 0.0));
+        let center = layout.with_alignment(Alignment::Center).layout(&font, "fox", glam::vec2(10.0, 0.0));
+        let right = layout.with_alignment(Alignment::Right).layout(&font, "fox", glam::vec2(10.0, 0.0));
+
+        assert_eq!(left[0].position.x(), 10.0);
+        let shift = right[0].position.x() - left[0].position.x();
+        assert!(shift > 100.0);
+        assert!((center[0].position.x() - left[0].position.x() - 0.5 * shift).abs() < 1e-3);
+
+        // First baseline sits at the ascender.
+        assert_eq!(left[0].position.y(), font.ascender() * 20.0 / font.units_per_em());
+    }
+
+    #[test]
+    fn kerning() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let kerned = Layout::new(20.0).layout(&font, "AV", glam::vec2(0.0, 0.0));
+        let plain = Layout::new(20.0).with_kerning(false).layout(&font, "AV", glam::vec2(0.0, 0.0));
+        assert!(kerned[1].position.x() < plain[1].position.x());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{math::clamp, Glyph};
+
+/// Stem darkening per pixel per em, horizontal and vertical (macOS style).
+const STEM_DARKENING_FACTOR: (f32, f32) = (0.0121, 0.0121 * 1.25);
+/// Upper limit of the stem darkening amount in pixels.
+const MAX_STEM_DARKENING: f32 = 0.3;
+/// Sizes above don't get darkened.
+const MAX_STEM_DARKENING_PIXELS_PER_EM: f32 = 72.0;
+
+/// Appearance controls applied when drawing glyphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextRenderOptions {
+    /// Embolden small glyphs depending on the font size.
+    pub stem_darkening: bool,
+    /// Exponent applied to the coverage, values above 1 thicken the text.
+    pub gamma: f32,
+    /// Boosts intermediate coverage values, 0 leaves the coverage untouched.
+    pub contrast: f32,
+}
+
+impl Default for TextRenderOptions {
+    fn default() -> Self {
+        TextRenderOptions {
+            stem_darkening: false,
+            gamma: 1.0,
+            contrast: 0.0,
+        }
+    }
+}
+
+impl TextRenderOptions {
+    /// Emboldening amount in pixels per side at `size` pixels per em.
+    pub fn stem_darkening_amount(&self, size: f32) -> glam::Vec2 {
+        if !self.stem_darkening || size > MAX_STEM_DARKENING_PIXELS_PER_EM {
+            return glam::vec2(0.0, 0.0);
+        }
+
+        glam::vec2(
+            (size * STEM_DARKENING_FACTOR.0).min(MAX_STEM_DARKENING),
+            (size * STEM_DARKENING_FACTOR.1).min(MAX_STEM_DARKENING),
+        )
+    }
+
+    /// Outline to rasterize for `glyph` at `size` pixels per em.
+    pub fn darken(&self, glyph: &Glyph, size: f32) -> Option<Glyph> {
+        let amount = self.stem_darkening_amount(size);
+        if amount == glam::vec2(0.0, 0.0) {
+            return None;
+        }
+
+        Some(glyph.embolden(amount * glyph.units_per_em / size))
+    }
+
+    pub fn adjusts_coverage(&self) -> bool {
+        self.gamma != 1.0 || self.contrast != 0.0
+    }
+
+    /// Map rasterized coverage to the final glyph coverage.
+    pub fn adjust_coverage(&self, coverage: f32) -> f32 {
+        let coverage = clamp(coverage, 0.0, 1.0).powf(1.0 / self.gamma);
+        clamp(coverage + self.contrast * coverage * (1.0 - coverage), 0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coverage_adjustment() {
+        let options = TextRenderOptions {
+            gamma: 1.8,
+            contrast: 0.5,
+            ..TextRenderOptions::default()
+        };
+
+        assert_eq!(options.adjust_coverage(0.0), 0.0);
+        assert_eq!(options.adjust_coverage(1.0), 1.0);
+        assert!(options.adjust_coverage(0.5) > 0.5);
+
+        let identity = TextRenderOptions::default();
+        assert!(!identity.adjusts_coverage());
+        assert_eq!(identity.adjust_coverage(0.3), 0.3);
+    }
+
+    #[test]
+    fn stem_darkening() {
+        let options = TextRenderOptions {
+            stem_darkening: true,
+            ..TextRenderOptions::default()
+        };
+
+        let small = options.stem_darkening_amount(12.0);
+        assert!(small.x() > 0.0 && small.y() > small.x());
+        assert_eq!(options.stem_darkening_amount(48.0), glam::vec2(MAX_STEM_DARKENING, MAX_STEM_DARKENING));
+        assert_eq!(options.stem_darkening_amount(96.0), glam::vec2(0.0, 0.0));
+    }
+}